@@ -0,0 +1,360 @@
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    io::{self, Write},
+    path::Path,
+    process,
+    time::{Duration, SystemTime},
+};
+
+use rando_kanji_core::{
+    anki::Anki,
+    archive::ProfileArchive,
+    config::{Config, ProfileId, StatValue},
+    deck::Deck,
+    kanji::KanjiRecord,
+    kanjidic::Kanjidic,
+    review_log::ReviewLog,
+    scheduler::ReviewSummary,
+    statistics::ProgressStatistics,
+    storage::{ProfileIndex, Storage},
+    validation::Validation,
+};
+
+const USAGE: &str = "\
+Usage: rando_kanji_cli [--data-dir <path>] <command>
+
+Commands:
+  profiles list            List the saved profiles
+  profile show <profile>   Show the settings of a profile
+  stats <profile>          Show the learning statistics of a profile
+  due <profile>            List the kanji due for a review
+  reset <profile> [--yes]  Delete the statistics and the review log of a profile,
+                           keeping its settings. Asks for confirmation without --yes
+  export <profile> <file>  Save a profile with its statistics and review log to a file
+  import <file>            Add an exported profile as a new profile
+  import <file> --into <profile>
+                           Merge an exported profile into a profile, keeping
+                           the most recent statistics of each kanji
+  deck validate <csv>      Check a kanji database or deck file and list the problems
+                           of each row, rows with errors are skipped when loading
+  kanjidic import <xml>    Build the extended kanji database with stroke counts,
+                           grades, frequency ranks, radicals, nanori and French,
+                           Spanish and Portuguese meanings from a KANJIDIC2 file
+  anki export <profile> <file>
+                           Write the deck of a profile with the scheduling state
+                           of each kanji as notes for importing into Anki
+  anki import <file> <deck name>
+                           Create a deck of the kanji notes in an Anki text export
+
+Profiles are given by their ID shown by `profiles list`, e.g. 1.
+The profiles are stored in the platform data directory unless another
+directory is given with --data-dir or the RANDO_KANJI_DATA_DIR variable";
+
+/// Non-interactive commands for inspecting and managing profiles and decks
+fn main() {
+    let args = match Storage::take_data_dir_arg(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("Error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+    let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    let result = match args.as_slice() {
+        ["profiles", "list"] => list_profiles(),
+        ["profile", "show", profile] => show_profile(profile),
+        ["stats", profile] => show_statistics(profile),
+        ["due", profile] => show_due_kanji(profile),
+        ["reset", profile, "--yes"] => reset_profile(profile, true),
+        ["reset", profile] => reset_profile(profile, false),
+        ["export", profile, path] => export_profile(profile, path),
+        ["import", path] => import_profile(path),
+        ["import", path, "--into", profile] => merge_profile(path, profile),
+        ["deck", "validate", path] => validate_deck(path),
+        ["kanjidic", "import", path] => import_kanjidic(path),
+        ["anki", "export", profile, path] => export_anki_notes(profile, path),
+        ["anki", "import", path, name] => import_anki_deck(path, name),
+        ["help"] | ["--help"] | ["-h"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn list_profiles() -> Result<(), Box<dyn Error>> {
+    let index = ProfileIndex::load();
+    for profile in index.profiles.iter().map(|profile| profile.id) {
+        match Config::try_load_by_profile(profile) {
+            Ok(config) => println!(
+                "{}  {}  ({} reviewed kanji){}",
+                profile.0,
+                config.profile.name,
+                config.answer_statistics.len(),
+                if index.last_used == Some(profile) {
+                    "  [last used]"
+                } else {
+                    ""
+                }
+            ),
+            Err(err) => println!("{}  could not be loaded: {}", profile.0, err),
+        }
+    }
+    Ok(())
+}
+
+fn show_profile(profile: &str) -> Result<(), Box<dyn Error>> {
+    let config = load_profile(profile)?;
+    let filter = &config.deck_filter;
+    let jlpt_levels = (0..filter.jlpt_levels.len())
+        .filter(|level| filter.jlpt_levels[*level])
+        .map(|level| {
+            if level == 0 {
+                "other".to_string()
+            } else {
+                format!("N{}", level)
+            }
+        })
+        .collect::<Vec<String>>();
+
+    println!("Name:                     {}", config.profile.name);
+    println!("Quiz mode:                {:?}", config.quiz_mode);
+    println!("Answer text:              {:?}", config.button_text_option);
+    println!("Wrong answer options:     {}", config.distractor_strategy);
+    println!("Scheduler:                {}", config.scheduler);
+    println!(
+        "Learning index threshold: {}",
+        config.learning_index_threshold
+    );
+    println!("Kanji pool max size:      {}", config.kanji_pool_max_size);
+    println!(
+        "Deck:                     {}",
+        config.deck.as_deref().unwrap_or(Deck::MAIN_DECK_NAME)
+    );
+    println!("JLPT levels:              {}", jlpt_levels.join(", "));
+    println!("Excluded kanji:           {}", filter.excluded_kanji);
+    println!("Show kanji details:       {}", config.show_kanji_details);
+    println!(
+        "Reviewed kanji:           {}",
+        config.answer_statistics.len()
+    );
+    println!(
+        "Reviewed reverse kanji:   {}",
+        config.reverse_statistics.len()
+    );
+    Ok(())
+}
+
+fn show_statistics(profile: &str) -> Result<(), Box<dyn Error>> {
+    let config = load_profile(profile)?;
+    let entries = ReviewLog::read_or_empty(&config.review_log_path())?;
+    let kanjis = Deck::load(config.deck.as_deref())?;
+    let statistics = ProgressStatistics::new(&entries, &config, &kanjis);
+
+    let correct = entries.iter().filter(|entry| entry.is_correct).count();
+    let accuracy = if entries.is_empty() {
+        0.0
+    } else {
+        correct as f64 / entries.len() as f64 * 100.0
+    };
+    let learned = |statistics: &HashMap<char, StatValue>| {
+        statistics
+            .values()
+            .filter(|stat| stat.learning_index >= config.learning_index_threshold)
+            .count()
+    };
+    let (learned_kanji, learned_reverse_kanji) = (
+        learned(&config.answer_statistics),
+        learned(&config.reverse_statistics),
+    );
+
+    println!("Total reviews:          {}", statistics.total_reviews);
+    println!("Accuracy:               {:.1} %", accuracy);
+    println!("Learned kanji:          {}", learned_kanji);
+    println!(
+        "Learning kanji:         {}",
+        config.answer_statistics.len() - learned_kanji
+    );
+    println!("Learned reverse kanji:  {}", learned_reverse_kanji);
+    println!(
+        "Learning reverse kanji: {}",
+        config.reverse_statistics.len() - learned_reverse_kanji
+    );
+    println!("JLPT levels, without the reverse mode:");
+    for level in statistics.jlpt_levels {
+        println!(
+            "  {:<8} learned {:>4}, learning {:>4}",
+            level.label(),
+            level.learned,
+            level.learning
+        );
+    }
+    Ok(())
+}
+
+fn show_due_kanji(profile: &str) -> Result<(), Box<dyn Error>> {
+    let config = load_profile(profile)?;
+    let now = SystemTime::now();
+    for (mode, statistics) in [
+        (
+            "Multiple choice and typed reading",
+            &config.answer_statistics,
+        ),
+        ("Reverse mode", &config.reverse_statistics),
+    ] {
+        println!("{}: {}", mode, ReviewSummary::new(statistics, now));
+        let mut due_kanji = statistics
+            .iter()
+            .filter(|(_, stat)| stat.is_due(now))
+            .collect::<Vec<_>>();
+        due_kanji.sort_unstable_by_key(|(_, stat)| stat.due_time);
+        for (kanji, stat) in due_kanji {
+            let overdue = now.duration_since(stat.due_time).unwrap_or_default();
+            println!("{}  overdue by {}", kanji, format_duration(overdue));
+        }
+    }
+    Ok(())
+}
+
+fn reset_profile(profile: &str, is_confirmed: bool) -> Result<(), Box<dyn Error>> {
+    let mut config = load_profile(profile)?;
+    if !is_confirmed
+        && !confirm(&format!(
+            "Delete all statistics and the review log of {}?",
+            config.profile.name
+        ))?
+    {
+        println!("{} was not reset", config.profile.name);
+        return Ok(());
+    }
+    config.answer_statistics.clear();
+    config.reverse_statistics.clear();
+    config.save()?;
+    ReviewLog::remove(&config.review_log_path());
+    println!("{} was reset", config.profile.name);
+    Ok(())
+}
+
+fn export_profile(profile: &str, path: &str) -> Result<(), Box<dyn Error>> {
+    let config = load_profile(profile)?;
+    ProfileArchive::export(&config, Path::new(path))?;
+    println!("{} was exported to {}", config.profile.name, path);
+    Ok(())
+}
+
+fn import_profile(path: &str) -> Result<(), Box<dyn Error>> {
+    let config = ProfileArchive::import(Path::new(path))?;
+    println!(
+        "{} was imported as profile {}",
+        config.profile.name, config.profile.id.0
+    );
+    Ok(())
+}
+
+fn merge_profile(path: &str, profile: &str) -> Result<(), Box<dyn Error>> {
+    let mut config = load_profile(profile)?;
+    let summary = ProfileArchive::merge_into(Path::new(path), &mut config)?;
+    println!("Merged into {}: {}", config.profile.name, summary);
+    Ok(())
+}
+
+fn validate_deck(path: &str) -> Result<(), Box<dyn Error>> {
+    let validation = Validation::check(Path::new(path))?;
+    for problem in validation.problems.iter() {
+        println!("{}", problem);
+    }
+    let errors = validation.error_count();
+    let warnings = validation.problems.len() - errors;
+    if errors == 0 {
+        println!("{} kanji, {} warnings", validation.kanjis.len(), warnings);
+        Ok(())
+    } else {
+        Err(format!(
+            "{} errors and {} warnings found, {} valid kanji",
+            errors,
+            warnings,
+            validation.kanjis.len()
+        )
+        .into())
+    }
+}
+
+fn import_kanjidic(path: &str) -> Result<(), Box<dyn Error>> {
+    let mut kanjis = KanjiRecord::from_csv_lenient(&Deck::bundled_db_path())?;
+    let found = Kanjidic::extend_records(Path::new(path), &mut kanjis)?;
+    let extended_path = Deck::extended_db_path();
+    KanjiRecord::to_csv(&extended_path, &kanjis)?;
+    println!(
+        "{} of {} kanji found in KANJIDIC2, written to {}",
+        found,
+        kanjis.len(),
+        extended_path.display()
+    );
+    Ok(())
+}
+
+fn export_anki_notes(profile: &str, path: &str) -> Result<(), Box<dyn Error>> {
+    let config = load_profile(profile)?;
+    let kanjis = Deck::load(config.deck.as_deref())?;
+    let count = Anki::export_notes(
+        Path::new(path),
+        &kanjis,
+        &config.answer_statistics,
+        SystemTime::now(),
+    )?;
+    println!("{} notes written to {}", count, path);
+    Ok(())
+}
+
+fn import_anki_deck(path: &str, name: &str) -> Result<(), Box<dyn Error>> {
+    let (deck, skipped) = Anki::import_deck(Path::new(path), name)?;
+    if !skipped.is_empty() {
+        println!(
+            "Skipped kanji missing from the kanji database: {}",
+            skipped.into_iter().collect::<String>()
+        );
+    }
+    println!("Deck {} was created as {}", deck.name, deck.file_name);
+    Ok(())
+}
+
+fn load_profile(profile: &str) -> Result<Config, Box<dyn Error>> {
+    let profile = profile
+        .parse::<u32>()
+        .map(ProfileId)
+        .map_err(|_| format!("Invalid profile ID: {}", profile))?;
+    Config::try_load_by_profile(profile)
+        .map_err(|err| format!("Could not load {}: {}", profile, err).into())
+}
+
+fn format_duration(duration: Duration) -> String {
+    const SECS_PER_HOUR: u64 = 60 * 60;
+    const SECS_PER_DAY: u64 = 24 * SECS_PER_HOUR;
+    let secs = duration.as_secs();
+    if secs >= SECS_PER_DAY {
+        format!("{} d", secs / SECS_PER_DAY)
+    } else if secs >= SECS_PER_HOUR {
+        format!("{} h", secs / SECS_PER_HOUR)
+    } else {
+        format!("{} min", secs / 60)
+    }
+}
+
+/// Ask a yes or no question on the terminal, no unless the answer starts with y
+fn confirm(question: &str) -> Result<bool, Box<dyn Error>> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().to_lowercase().starts_with('y'))
+}
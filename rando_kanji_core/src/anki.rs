@@ -0,0 +1,285 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    time::SystemTime,
+};
+
+use crate::{
+    config::StatValue,
+    deck::{Deck, DeckInfo},
+    kanji::KanjiRecord,
+};
+
+/// Tab-separated notes files in the plain text format of Anki
+pub struct Anki;
+
+impl Anki {
+    const COLUMNS: [&'static str; 10] = [
+        "Kanji",
+        "Reading",
+        "On",
+        "On meaning",
+        "Kun",
+        "Kun meaning",
+        "JLPT",
+        "Interval days",
+        "Due in days",
+        "Tags",
+    ];
+    const TAG: &'static str = "rando_kanji";
+    const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+    /// Write a note for each kanji of the deck, with the scheduling state of the reviewed kanji.
+    /// Returns the number of notes
+    pub fn export_notes(
+        path: &Path,
+        kanjis: &[KanjiRecord],
+        statistics: &HashMap<char, StatValue>,
+        now: SystemTime,
+    ) -> Result<usize, Box<dyn Error>> {
+        let mut file = BufWriter::new(File::create(path)?);
+        // File headers understood by the Anki import dialog
+        writeln!(file, "#separator:tab")?;
+        writeln!(file, "#html:false")?;
+        writeln!(file, "#columns:{}", Self::COLUMNS.join("\t"))?;
+        writeln!(file, "#tags column:{}", Self::COLUMNS.len())?;
+
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(false)
+            .from_writer(file);
+        for record in kanjis {
+            let stat = statistics.get(&record.kanji);
+            let (interval, due) = match stat {
+                Some(stat) if !stat.is_learning() => (
+                    format!(
+                        "{:.1}",
+                        stat.review_interval.as_secs_f64() / Self::SECONDS_PER_DAY
+                    ),
+                    format!("{:.1}", Self::days_until(stat.due_time, now)),
+                ),
+                _ => (String::new(), String::new()),
+            };
+            let state = match stat {
+                None => "new",
+                Some(stat) if stat.is_learning() => "learning",
+                Some(stat) if stat.is_due(now) => "due",
+                Some(_) => "learned",
+            };
+            let jlpt = match record.jlpt {
+                0 => String::new(),
+                level => format!("N{}", level),
+            };
+            writer.write_record([
+                record.kanji.to_string().as_str(),
+                record.joyo_reading.trim(),
+                record.on_reading.trim(),
+                record.on_trans.trim(),
+                record.kun_reading.trim(),
+                record.kun_trans.trim(),
+                &jlpt,
+                &interval,
+                &due,
+                &format!(
+                    "{} {}::{} {}::jlpt_{}",
+                    Self::TAG,
+                    Self::TAG,
+                    state,
+                    Self::TAG,
+                    record.jlpt
+                ),
+            ])?;
+        }
+        writer.flush()?;
+        Ok(kanjis.len())
+    }
+
+    /// Create a deck of the kanji notes in an Anki plain text export. The kanji of a note is the
+    /// first field holding a single kanji, and only kanji of the main database are included.
+    /// Returns the deck and the kanji that were skipped
+    pub fn import_deck(
+        path: &Path,
+        deck_name: &str,
+    ) -> Result<(DeckInfo, Vec<char>), Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        let mut separator = b'\t';
+        let mut is_html = false;
+        for header in text.lines().map_while(|line| line.strip_prefix('#')) {
+            match header.split_once(':') {
+                Some(("separator", value)) => separator = Self::parse_separator(value)?,
+                Some(("html", value)) => is_html = value.trim() == "true",
+                _ => {}
+            }
+        }
+
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(separator)
+            .comment(Some(b'#'))
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(text.as_bytes());
+        let mut note_kanji = Vec::new();
+        for note in reader.records() {
+            let note = note?;
+            let kanji = note.iter().find_map(|field| {
+                let field = if is_html {
+                    Self::strip_html(field)
+                } else {
+                    field.to_string()
+                };
+                let mut chars = field.trim().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(kanji), None) if Self::is_kanji(kanji) => Some(kanji),
+                    _ => None,
+                }
+            });
+            if let Some(kanji) = kanji {
+                if !note_kanji.contains(&kanji) {
+                    note_kanji.push(kanji);
+                }
+            }
+        }
+
+        let main_kanjis = Deck::load(None)?;
+        let (kanji, skipped): (Vec<char>, Vec<char>) = note_kanji
+            .into_iter()
+            .partition(|kanji| main_kanjis.iter().any(|record| record.kanji == *kanji));
+        let deck = Deck::create(deck_name, &kanji.into_iter().collect::<String>())?;
+        Ok((deck, skipped))
+    }
+
+    fn parse_separator(value: &str) -> Result<u8, Box<dyn Error>> {
+        match value.trim().to_lowercase().as_str() {
+            "tab" => Ok(b'\t'),
+            "comma" => Ok(b','),
+            "semicolon" => Ok(b';'),
+            "space" => Ok(b' '),
+            "pipe" => Ok(b'|'),
+            "colon" => Ok(b':'),
+            other if other.len() == 1 => Ok(other.as_bytes()[0]),
+            other => Err(format!("Unknown separator: {}", other).into()),
+        }
+    }
+
+    /// Text of an HTML field without tags and with the common entities replaced
+    fn strip_html(field: &str) -> String {
+        let mut text = String::with_capacity(field.len());
+        let mut is_tag = false;
+        for c in field.chars() {
+            match c {
+                '<' => is_tag = true,
+                '>' => is_tag = false,
+                c if !is_tag => text.push(c),
+                _ => {}
+            }
+        }
+        text.replace("&nbsp;", " ")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&")
+    }
+
+    #[inline]
+    fn is_kanji(c: char) -> bool {
+        matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}')
+    }
+
+    fn days_until(time: SystemTime, now: SystemTime) -> f64 {
+        match time.duration_since(now) {
+            Ok(duration) => duration.as_secs_f64() / Self::SECONDS_PER_DAY,
+            Err(err) => -err.duration().as_secs_f64() / Self::SECONDS_PER_DAY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::storage::Storage;
+
+    const A: &str = "1;亜;1;ア;;a;rank next, come after, Asia, sub-, -ous (in acids);-;-";
+    const KOU: &str = "589;公;2;コウ、おおやけ;ク、グ、きみ;koo;\"public, duke, lord; public\";ooyake;\"public, open; official\"";
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    #[test]
+    fn export_notes_writes_the_scheduling_state() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100 * DAY);
+        let learned = StatValue {
+            learning_index: 5,
+            last_review_time: now - Duration::from_secs(DAY),
+            review_interval: Duration::from_secs(3 * DAY),
+            due_time: now + Duration::from_secs(2 * DAY),
+            ..StatValue::default()
+        };
+        let statistics = HashMap::from([('亜', learned)]);
+        let kanjis = [A, KOU].map(KanjiRecord::from_row);
+        let path = Storage::use_test_data_dir().join("anki_export.txt");
+        Storage::create_parent_dir(&path).unwrap();
+
+        assert_eq!(
+            Anki::export_notes(&path, &kanjis, &statistics, now).unwrap(),
+            2
+        );
+        let text = fs::read_to_string(&path).unwrap();
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "#separator:tab");
+        assert_eq!(lines[3], "#tags column:10");
+        assert_eq!(
+            lines[4].split('\t').collect::<Vec<_>>(),
+            [
+                "亜",
+                "ア",
+                "a",
+                "rank next, come after, Asia, sub-, -ous (in acids)",
+                "-",
+                "-",
+                "N1",
+                "3.0",
+                "2.0",
+                "rando_kanji rando_kanji::learned rando_kanji::jlpt_1"
+            ]
+        );
+        let fields = lines[5].split('\t').collect::<Vec<_>>();
+        assert_eq!(
+            fields[7..],
+            ["", "", "rando_kanji rando_kanji::new rando_kanji::jlpt_2"]
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn import_deck_takes_the_first_kanji_field_of_each_note() {
+        let path = Storage::use_test_data_dir().join("anki_import.txt");
+        Storage::create_parent_dir(&path).unwrap();
+        let notes = "#separator:semicolon\n#html:true\n\
+                     front;<b>上</b>&nbsp;;joo\n\
+                     公;koo\n\
+                     亜;again\n\
+                     上;duplicate\n\
+                     note without kanji;abc\n\
+                     龘;not in the database\n\
+                     哀;aware\n\
+                     愛;ai\n";
+        fs::write(&path, notes).unwrap();
+
+        let (deck, skipped) = Anki::import_deck(&path, "Anki import").unwrap();
+        assert_eq!(deck.name, "Anki import");
+        assert_eq!(skipped, ['龘']);
+        let mut kanji = Deck::load(Some(&deck.file_name))
+            .unwrap()
+            .iter()
+            .map(|record| record.kanji)
+            .collect::<Vec<_>>();
+        kanji.sort_unstable();
+        let mut expected = ['上', '公', '亜', '哀', '愛'];
+        expected.sort_unstable();
+        assert_eq!(kanji, expected);
+        fs::remove_file(&path).unwrap();
+    }
+}
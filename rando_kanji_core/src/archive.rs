@@ -0,0 +1,154 @@
+use std::{collections::HashMap, error::Error, fmt::Display, fs::File, io::BufReader, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    config::{Config, StatValue},
+    migration::ProfileMigration,
+    review_log::{ReviewLog, ReviewLogEntry},
+    storage::Storage,
+};
+
+/// A profile with its settings, statistics and review log in a single file
+/// for moving the progress of a learner to another machine
+#[derive(Serialize, Deserialize)]
+pub struct ProfileArchive {
+    pub version: u32,
+    pub config: Value, // Upgraded on import like a profile file of an older version
+    pub reviews: Vec<ReviewLogEntry>,
+}
+
+/// Changes made by merging an archive into a profile
+pub struct MergeSummary {
+    pub updated_kanji: usize,
+    pub added_reviews: usize,
+}
+
+impl Display for MergeSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} kanji updated, {} reviews added",
+            self.updated_kanji, self.added_reviews
+        )
+    }
+}
+
+impl ProfileArchive {
+    pub const FILE_EXTENSION: &'static str = ".rkprofile";
+    const CURRENT_VERSION: u32 = 1;
+
+    pub fn export(config: &Config, path: &Path) -> Result<(), Box<dyn Error>> {
+        let archive = Self {
+            version: Self::CURRENT_VERSION,
+            config: serde_json::to_value(config)?,
+            reviews: ReviewLog::read_or_empty(&config.review_log_path())?,
+        };
+        Storage::write_json(path, &archive)
+    }
+
+    /// Import the archive as a new profile
+    pub fn import(path: &Path) -> Result<Config, Box<dyn Error>> {
+        let (mut config, reviews) = Self::from_file(path)?;
+        config.profile.id = Config::next_profile_id();
+        ReviewLog::write(&config.review_log_path(), &reviews)?;
+        config.save()?;
+        Ok(config)
+    }
+
+    /// Merge the statistics and the review log of the archive into a profile,
+    /// keeping the most recently reviewed statistics of each kanji. The settings of the profile are kept
+    pub fn merge_into(path: &Path, config: &mut Config) -> Result<MergeSummary, Box<dyn Error>> {
+        let (archived_config, reviews) = Self::from_file(path)?;
+        let updated_kanji = Self::merge_statistics(
+            &mut config.answer_statistics,
+            archived_config.answer_statistics,
+        ) + Self::merge_statistics(
+            &mut config.reverse_statistics,
+            archived_config.reverse_statistics,
+        );
+
+        let review_log_path = config.review_log_path();
+        let mut entries = ReviewLog::read_or_empty(&review_log_path)?;
+        let added_reviews = ReviewLog::merge(&mut entries, reviews);
+        ReviewLog::write(&review_log_path, &entries)?;
+        config.save()?;
+        Ok(MergeSummary {
+            updated_kanji,
+            added_reviews,
+        })
+    }
+
+    fn from_file(path: &Path) -> Result<(Config, Vec<ReviewLogEntry>), Box<dyn Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut archive: Self = serde_json::from_reader(reader)?;
+        if archive.version > Self::CURRENT_VERSION {
+            return Err(format!(
+                "Archive version {} is newer than the supported version {}, please update the app",
+                archive.version,
+                Self::CURRENT_VERSION
+            )
+            .into());
+        }
+        ProfileMigration::migrate(&mut archive.config)?;
+        Ok((serde_json::from_value(archive.config)?, archive.reviews))
+    }
+
+    /// Returns the number of kanji taken from the other statistics
+    fn merge_statistics(
+        statistics: &mut HashMap<char, StatValue>,
+        other: HashMap<char, StatValue>,
+    ) -> usize {
+        let mut updated_kanji = 0;
+        for (kanji, stat) in other {
+            let is_newer = match statistics.get(&kanji) {
+                Some(current) => stat.last_review_time > current.last_review_time,
+                None => true,
+            };
+            if is_newer {
+                statistics.insert(kanji, stat);
+                updated_kanji += 1;
+            }
+        }
+        updated_kanji
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    fn stat(last_review_secs: u64, learning_index: i32) -> StatValue {
+        StatValue {
+            learning_index,
+            last_review_time: SystemTime::UNIX_EPOCH + Duration::from_secs(last_review_secs),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_statistics_keeps_the_latest_answered_kanji() {
+        let mut statistics = HashMap::from([('亜', stat(100, 1)), ('哀', stat(300, 2))]);
+        let other = HashMap::from([
+            ('亜', stat(200, 5)), // Newer
+            ('哀', stat(200, 6)), // Older
+            ('愛', stat(100, 7)), // Missing
+        ]);
+        assert_eq!(ProfileArchive::merge_statistics(&mut statistics, other), 2);
+        assert_eq!(statistics.len(), 3);
+        assert_eq!(statistics[&'亜'].learning_index, 5);
+        assert_eq!(statistics[&'哀'].learning_index, 2);
+        assert_eq!(statistics[&'愛'].learning_index, 7);
+    }
+
+    #[test]
+    fn merge_statistics_keeps_kanji_answered_at_the_same_time() {
+        let mut statistics = HashMap::from([('亜', stat(100, 1))]);
+        let other = HashMap::from([('亜', stat(100, 5))]);
+        assert_eq!(ProfileArchive::merge_statistics(&mut statistics, other), 0);
+        assert_eq!(statistics[&'亜'].learning_index, 1);
+    }
+}
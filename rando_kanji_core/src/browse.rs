@@ -0,0 +1,259 @@
+use std::fmt::Display;
+
+use crate::{config::Config, kanji::KanjiRecord, romaji};
+
+/// Learning state of a kanji in the current quiz mode of a profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KanjiState {
+    New,
+    Learning,
+    Learned,
+    Suspended, // Excluded from the deck of the profile
+}
+
+impl KanjiState {
+    pub const ALL: [KanjiState; 4] = [Self::New, Self::Learning, Self::Learned, Self::Suspended];
+
+    pub fn of(kanji: char, config: &Config) -> Self {
+        if config.deck_filter.excludes(kanji) {
+            return Self::Suspended;
+        }
+        match config.statistics().get(&kanji) {
+            None => Self::New,
+            Some(stat) if stat.learning_index >= config.learning_index_threshold => Self::Learned,
+            Some(_) => Self::Learning,
+        }
+    }
+}
+
+impl Display for KanjiState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::New => "New",
+            Self::Learning => "Learning",
+            Self::Learned => "Learned",
+            Self::Suspended => "Suspended",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Order of the kanji browser results. The attributes other than the deck order come from
+/// the extended kanji database, kanji without them are sorted last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KanjiSort {
+    #[default]
+    Deck,
+    Strokes,
+    Grade,
+    Frequency,
+}
+
+impl KanjiSort {
+    pub const ALL: [KanjiSort; 4] = [Self::Deck, Self::Strokes, Self::Grade, Self::Frequency];
+
+    fn key(self, record: &KanjiRecord) -> Option<u16> {
+        match self {
+            Self::Deck => Some(0),
+            Self::Strokes => record.strokes.map(u16::from),
+            Self::Grade => record.grade.map(u16::from),
+            Self::Frequency => record.frequency,
+        }
+    }
+}
+
+impl Display for KanjiSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Deck => "Deck order",
+            Self::Strokes => "Stroke count",
+            Self::Grade => "School grade",
+            Self::Frequency => "Frequency",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Search and filters of the kanji browser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KanjiSearch {
+    pub query: String, // Kanji, kana, rōmaji or a meaning in any language of the database
+    pub jlpt_levels: [bool; 6], // Shown JLPT levels indexed by level, 0 for kanji outside of the JLPT
+    pub state: Option<KanjiState>, // All states if none
+    pub grade: Option<u8>,      // School grade of the extended database, all grades if none
+    pub sort: KanjiSort,
+}
+
+impl Default for KanjiSearch {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            jlpt_levels: [true; 6],
+            state: None,
+            grade: None,
+            sort: KanjiSort::default(),
+        }
+    }
+}
+
+impl KanjiSearch {
+    /// The kanji that match the search in the chosen order
+    pub fn results(&self, kanjis: &[KanjiRecord], config: &Config) -> Vec<char> {
+        let query = self.query.trim().to_lowercase();
+        let mut results = kanjis
+            .iter()
+            .filter(|record| {
+                self.jlpt_levels
+                    .get(record.jlpt as usize)
+                    .copied()
+                    .unwrap_or(true)
+            })
+            .filter(|record| match self.state {
+                Some(state) => KanjiState::of(record.kanji, config) == state,
+                None => true,
+            })
+            .filter(|record| self.grade.is_none() || record.grade == self.grade)
+            .filter(|record| query.is_empty() || Self::matches(record, &query))
+            .collect::<Vec<&KanjiRecord>>();
+        // The sort is stable so that kanji with the same key stay in the order of the deck
+        results.sort_by_key(|record| match self.sort.key(record) {
+            Some(key) => (false, key),
+            None => (true, 0),
+        });
+        results.iter().map(|record| record.kanji).collect()
+    }
+
+    /// Kanji in the query match themselves, kana and rōmaji match the start of a reading
+    /// or name reading and other text matches a part of a meaning
+    fn matches(record: &KanjiRecord, query: &str) -> bool {
+        if query.contains(record.kanji) {
+            return true;
+        }
+        let readings = record.readings();
+        let kana = romaji::katakana_to_hiragana(query);
+        let is_kana = kana.chars().all(|c| matches!(c, 'ぁ'..='ゖ' | 'ー'));
+        if is_kana {
+            return readings
+                .on
+                .iter()
+                .chain(readings.kun.iter())
+                .map(|reading| romaji::katakana_to_hiragana(&reading.full()))
+                .chain(record.nanori.split('、').map(romaji::katakana_to_hiragana))
+                .any(|reading| !reading.is_empty() && reading.starts_with(&kana));
+        }
+        readings
+            .on_romaji
+            .iter()
+            .chain(readings.kun_romaji.iter())
+            .any(|reading| reading.full().starts_with(query))
+            || readings
+                .meanings
+                .iter()
+                .chain([&record.fr_trans, &record.es_trans, &record.pt_trans])
+                .any(|meaning| meaning.to_lowercase().contains(query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StatValue;
+
+    const JOU: &str = "1009;上;5;ジョウ、[ショウ]、うえ、[うわ]、かみ、あ-げる、あ-がる;;joo, [shoo];\"top; best; first volume. upper part; best\";ue, [uwa], kami, a(geru), a(garu);\"up, upper part, top. rise, go up.\"";
+    const A: &str = "1;亜;1;ア;;a;rank next, come after, Asia, sub-, -ous (in acids);-;-";
+    const KOU: &str = "589;公;2;コウ、おおやけ;ク、グ、きみ;koo;\"public, duke, lord; public\";ooyake;\"public, open; official\"";
+
+    fn kanjis() -> Vec<KanjiRecord> {
+        let mut kanjis = [A, KOU, JOU].map(KanjiRecord::from_row).to_vec();
+        kanjis[0].strokes = Some(7);
+        kanjis[0].frequency = Some(1509);
+        kanjis[1].strokes = Some(4);
+        kanjis[1].grade = Some(2);
+        kanjis[1].nanori = "きん、ただ".to_string();
+        kanjis[1].fr_trans = "Public, Prince".to_string();
+        kanjis[2].strokes = Some(3);
+        kanjis[2].grade = Some(1);
+        kanjis[2].frequency = Some(35);
+        kanjis
+    }
+
+    fn search(query: &str) -> Vec<char> {
+        let search = KanjiSearch {
+            query: query.to_string(),
+            ..KanjiSearch::default()
+        };
+        search.results(&kanjis(), &Config::default())
+    }
+
+    #[test]
+    fn results_match_kanji_readings_and_meanings() {
+        assert_eq!(search(""), ['亜', '公', '上']);
+        assert_eq!(search("上公"), ['公', '上']);
+        // Kana match the start of the jōyō readings and name readings, in hiragana or katakana
+        assert_eq!(search("じょ"), ['上']);
+        assert_eq!(search("コ"), ['公']);
+        assert_eq!(search("ただ"), ['公']);
+        assert_eq!(search("ょう"), Vec::<char>::new());
+        // Rōmaji match the start of the rōmaji readings
+        assert_eq!(search(" Ooya "), ['公']);
+        assert_eq!(search("aga"), ['上']);
+        // Other text matches a part of the meanings in any language
+        assert_eq!(search("asia"), ['亜']);
+        assert_eq!(search("prince"), ['公']);
+        assert_eq!(search("up"), ['上']);
+    }
+
+    #[test]
+    fn results_are_filtered_by_jlpt_level_state_and_grade() {
+        let mut config = Config::default();
+        config.answer_statistics.insert('亜', StatValue::default());
+        config.answer_statistics.insert(
+            '上',
+            StatValue {
+                learning_index: config.learning_index_threshold,
+                ..StatValue::default()
+            },
+        );
+        config.deck_filter.excluded_kanji = "公".to_string();
+        let results = |search: KanjiSearch| search.results(&kanjis(), &config);
+
+        let mut jlpt_levels = [true; 6];
+        jlpt_levels[1] = false;
+        assert_eq!(
+            results(KanjiSearch {
+                jlpt_levels,
+                ..KanjiSearch::default()
+            }),
+            ['公', '上']
+        );
+        let states = KanjiState::ALL.map(|state| {
+            results(KanjiSearch {
+                state: Some(state),
+                ..KanjiSearch::default()
+            })
+        });
+        assert_eq!(states, [vec![], vec!['亜'], vec!['上'], vec!['公']]);
+        assert_eq!(
+            results(KanjiSearch {
+                grade: Some(2),
+                ..KanjiSearch::default()
+            }),
+            ['公']
+        );
+    }
+
+    #[test]
+    fn results_without_the_sort_attribute_come_last_in_deck_order() {
+        let sorted = |sort: KanjiSort| {
+            KanjiSearch {
+                sort,
+                ..KanjiSearch::default()
+            }
+            .results(&kanjis(), &Config::default())
+        };
+        assert_eq!(sorted(KanjiSort::Deck), ['亜', '公', '上']);
+        assert_eq!(sorted(KanjiSort::Strokes), ['上', '公', '亜']);
+        assert_eq!(sorted(KanjiSort::Grade), ['上', '公', '亜']);
+        assert_eq!(sorted(KanjiSort::Frequency), ['上', '亜', '公']);
+    }
+}
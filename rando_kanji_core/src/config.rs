@@ -0,0 +1,404 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    fs::{self, File},
+    io::{BufReader, ErrorKind},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    distractor::DistractorStrategy,
+    kanji::KanjiRecord,
+    migration::ProfileMigration,
+    review_log::ReviewLog,
+    scheduler::{SchedulerKind, Sm2Scheduler},
+    storage::{ProfileIndex, Storage},
+};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Config {
+    pub version: u32, // Profile file format version, older profiles are upgraded when loaded
+    pub profile: Profile,
+    #[serde(default)]
+    pub quiz_mode: QuizMode,
+    pub button_text_option: ButtonTextOption,
+    pub learning_index_threshold: i32, // Value of learning index for a kanji to be considered learned
+    pub kanji_pool_max_size: u32,      // Size of the kanji pool at the start of a new pool cycle
+    #[serde(default)]
+    pub scheduler: SchedulerKind,
+    #[serde(default)]
+    pub distractor_strategy: DistractorStrategy,
+    #[serde(default)]
+    pub deck: Option<String>, // File name of a custom deck, the main kanji database if none
+    #[serde(default)]
+    pub deck_filter: DeckFilter,
+    #[serde(default = "Config::default_show_kanji_details")]
+    pub show_kanji_details: bool, // Show the full entry of a kanji after it is answered correctly
+    pub answer_statistics: HashMap<char, StatValue>,
+    #[serde(default)]
+    pub reverse_statistics: HashMap<char, StatValue>, // Statistics of the reverse quiz mode
+}
+
+impl Config {
+    pub const FILE_PREFIX: &'static str = "config";
+    pub const FILE_EXTENSION: &'static str = ".json";
+
+    fn from_file(path: &Path) -> Result<Config, Box<dyn Error>> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let mut value: Value = serde_json::from_reader(reader)?;
+        let version = ProfileMigration::migrate(&mut value)?;
+        let config: Config = serde_json::from_value(value)?;
+        if version < ProfileMigration::CURRENT_VERSION {
+            // Keep the file of the old version in case the migration lost anything
+            let backup_path = path.with_extension(format!("v{}.bak", version));
+            match fs::copy(path, &backup_path) {
+                Ok(_) => match config.to_file(path) {
+                    Ok(()) => {
+                        eprintln!("Migrated {} to version {}", path.display(), config.version)
+                    }
+                    Err(err) => eprintln!("Error saving migrated profile: {}", err),
+                },
+                Err(err) => eprintln!("Error backing up profile before migrating it: {}", err),
+            }
+        }
+        Ok(config)
+    }
+
+    #[inline]
+    fn to_file(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        Storage::write_json(path, self)
+    }
+
+    pub fn file_name(profile: ProfileId) -> String {
+        format!("{}{}{}", Self::FILE_PREFIX, profile.0, Self::FILE_EXTENSION)
+    }
+
+    pub fn review_log_file_name(profile: ProfileId) -> String {
+        format!(
+            "{}{}{}",
+            Self::FILE_PREFIX,
+            profile.0,
+            ReviewLog::FILE_EXTENSION
+        )
+    }
+
+    #[inline]
+    fn path(&self) -> PathBuf {
+        Storage::path(&Self::file_name(self.profile.id))
+    }
+
+    #[inline]
+    pub fn review_log_path(&self) -> PathBuf {
+        Storage::path(&Self::review_log_file_name(self.profile.id))
+    }
+
+    /// Statistics of the current quiz mode, recognition and recall of kanji are scored separately
+    #[inline]
+    pub fn statistics(&self) -> &HashMap<char, StatValue> {
+        if self.quiz_mode.is_reverse() {
+            &self.reverse_statistics
+        } else {
+            &self.answer_statistics
+        }
+    }
+
+    #[inline]
+    pub fn statistics_mut(&mut self) -> &mut HashMap<char, StatValue> {
+        if self.quiz_mode.is_reverse() {
+            &mut self.reverse_statistics
+        } else {
+            &mut self.answer_statistics
+        }
+    }
+
+    /// Save the profile and add it to the profile index. The previous save is backed up
+    /// at most once per backup interval
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = self.path();
+        if let Err(err) = Storage::rotate_backups(&path, SystemTime::now()) {
+            eprintln!("Error backing up profile: {}", err);
+        }
+        self.to_file(&path)?;
+        let mut index = ProfileIndex::load();
+        index.update(&self.profile);
+        index.save()
+    }
+
+    /// Load the profile used last, or any saved profile if it can't be loaded
+    pub fn load_from_file() -> Self {
+        let index = ProfileIndex::load();
+        let profiles = index
+            .last_used
+            .into_iter()
+            .chain(index.profiles.iter().map(|profile| profile.id));
+        for profile in profiles {
+            match Self::try_load_by_profile(profile) {
+                Ok(config) => return config,
+                Err(err) => eprintln!("Could not load {}: {}", profile, err),
+            }
+        }
+        eprintln!("Could not load config from file");
+        // Use a new ID so that saving the default profile never overwrites an unreadable profile
+        Config {
+            profile: Profile {
+                id: Self::next_profile_id(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    pub fn try_load_by_profile(profile: ProfileId) -> Result<Config, Box<dyn Error>> {
+        Self::from_file(&Storage::path(&Self::file_name(profile)))
+    }
+
+    /// Remember the profile to be loaded on the next start
+    pub fn mark_last_used(&self) {
+        let mut index = ProfileIndex::load();
+        index.last_used = Some(self.profile.id);
+        if let Err(err) = index.save() {
+            eprintln!("Error saving profile index: {}", err);
+        }
+    }
+
+    /// IDs of the saved profiles in ascending order
+    pub fn profile_ids() -> Vec<ProfileId> {
+        Self::profiles().iter().map(|profile| profile.id).collect()
+    }
+
+    /// Names and IDs of the saved profiles
+    #[inline]
+    pub fn profiles() -> Vec<Profile> {
+        ProfileIndex::load().profiles
+    }
+
+    /// Create and save a new profile with default settings
+    pub fn create_profile(name: &str) -> Result<Config, Box<dyn Error>> {
+        let config = Config {
+            profile: Profile {
+                name: name.to_string(),
+                id: Self::next_profile_id(),
+            },
+            ..Default::default()
+        };
+        config.save()?;
+        Ok(config)
+    }
+
+    /// Save a copy of the profile with its statistics and review log under a new ID
+    pub fn duplicate_profile(&self, name: &str) -> Result<Config, Box<dyn Error>> {
+        let mut config = self.clone();
+        config.profile = Profile {
+            name: name.to_string(),
+            id: Self::next_profile_id(),
+        };
+        config.save()?;
+        if let Err(err) = fs::copy(self.review_log_path(), config.review_log_path()) {
+            if err.kind() != ErrorKind::NotFound {
+                eprintln!("Error copying review log: {}", err);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Remove the profile file, its backups and the review log of the profile
+    pub fn delete_profile(profile: ProfileId) -> Result<(), Box<dyn Error>> {
+        let path = Storage::path(&Self::file_name(profile));
+        if let Err(err) = fs::remove_file(&path) {
+            if err.kind() != ErrorKind::NotFound {
+                return Err(err.into());
+            }
+        }
+        Storage::remove_backups(&path);
+        ReviewLog::remove(&Storage::path(&Self::review_log_file_name(profile)));
+        let mut index = ProfileIndex::load();
+        index.remove(profile);
+        index.save()
+    }
+
+    pub(crate) fn next_profile_id() -> ProfileId {
+        let mut index = ProfileIndex::load();
+        let id = index.take_next_id();
+        if let Err(err) = index.save() {
+            eprintln!("Error saving profile index: {}", err);
+        }
+        id
+    }
+
+    fn default_show_kanji_details() -> bool {
+        true
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: ProfileMigration::CURRENT_VERSION,
+            profile: Profile::default(),
+            quiz_mode: QuizMode::default(),
+            button_text_option: ButtonTextOption::Kana,
+            learning_index_threshold: 5,
+            kanji_pool_max_size: 10,
+            scheduler: SchedulerKind::default(),
+            distractor_strategy: DistractorStrategy::default(),
+            deck: None,
+            deck_filter: DeckFilter::default(),
+            show_kanji_details: Config::default_show_kanji_details(),
+            answer_statistics: HashMap::with_capacity(10),
+            reverse_statistics: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StatValue {
+    pub learning_index: i32,
+    pub last_review_time: SystemTime, // Time of the last answer
+    pub review_interval: Duration, // Time between reviews, zero while the kanji is still being learned
+    #[serde(default = "StatValue::default_ease_factor")]
+    pub ease_factor: f32,
+    #[serde(default)]
+    pub repetitions: u32, // Number of successful reviews in a row
+    #[serde(default)]
+    pub lapses: u32, // Number of times the kanji has been forgotten after being learned
+    #[serde(default = "StatValue::default_due_time")]
+    pub due_time: SystemTime,
+    #[serde(default)]
+    pub leitner_box: u8, // Leitner scheduler box, zero while the kanji is being learned
+    #[serde(default)]
+    pub stability: f32, // FSRS memory stability in days
+    #[serde(default)]
+    pub difficulty: f32, // FSRS difficulty between 1 and 10
+}
+
+impl StatValue {
+    /// Kanji that have not yet graduated to spaced reviews are drilled in the kanji pool
+    #[inline]
+    pub fn is_learning(&self) -> bool {
+        self.review_interval.is_zero()
+    }
+
+    #[inline]
+    pub fn is_due(&self, now: SystemTime) -> bool {
+        !self.is_learning() && self.due_time <= now
+    }
+
+    fn default_ease_factor() -> f32 {
+        Sm2Scheduler::DEFAULT_EASE_FACTOR
+    }
+
+    fn default_due_time() -> SystemTime {
+        SystemTime::UNIX_EPOCH
+    }
+}
+
+impl Default for StatValue {
+    fn default() -> Self {
+        StatValue {
+            learning_index: 0,
+            last_review_time: SystemTime::now(),
+            review_interval: Duration::default(),
+            ease_factor: Self::default_ease_factor(),
+            repetitions: 0,
+            lapses: 0,
+            due_time: Self::default_due_time(),
+            leitner_box: 0,
+            stability: 0.0,
+            difficulty: 0.0,
+        }
+    }
+}
+
+/// Limits which kanji are introduced as new kanji and used as answer options
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DeckFilter {
+    pub jlpt_levels: [bool; 6], // Included JLPT levels indexed by level, 0 for kanji outside of the JLPT
+    pub excluded_kanji: String, // Kanji that are never dealt, other characters are ignored
+}
+
+impl DeckFilter {
+    #[inline]
+    pub fn includes(&self, record: &KanjiRecord) -> bool {
+        self.jlpt_levels
+            .get(record.jlpt as usize)
+            .copied()
+            .unwrap_or(true)
+            && !self.excludes(record.kanji)
+    }
+
+    #[inline]
+    pub fn excludes(&self, kanji: char) -> bool {
+        self.excluded_kanji.contains(kanji)
+    }
+}
+
+impl Default for DeckFilter {
+    fn default() -> Self {
+        Self {
+            jlpt_levels: [true; 6],
+            excluded_kanji: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ButtonTextOption {
+    Kana,
+    Romaji,
+    Meaning,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+pub enum QuizMode {
+    #[default]
+    MultipleChoice,
+    TypedReading, // Type the reading in rōmaji which is converted to kana
+    Reverse,      // Show the meaning or reading and choose the correct kanji
+}
+
+impl QuizMode {
+    #[inline]
+    pub fn is_reverse(self) -> bool {
+        self == QuizMode::Reverse
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub id: ProfileId,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: "Default profile".to_string(),
+            id: ProfileId::default(),
+        }
+    }
+}
+
+/// Stable identifier of a profile, also used in the profile file names
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProfileId(pub u32);
+
+impl Default for ProfileId {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl Display for ProfileId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Profile {}", self.0)
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{kanji::KanjiRecord, storage::Storage};
+
+/// A user-defined deck file in the decks directory of the data directory.
+/// CSV decks contain full kanji records in the same format as the main kanji database,
+/// JSON decks pick a subset of the main database
+pub struct DeckInfo {
+    pub file_name: String,
+    pub name: String,
+}
+
+/// Contents of a JSON deck file, e.g. `{ "name": "Chapter 5", "kanji": "日月火水木" }`
+#[derive(Serialize, Deserialize)]
+struct DeckSpec {
+    name: String,
+    kanji: String,
+}
+
+pub struct Deck;
+
+impl Deck {
+    pub const DIR_NAME: &'static str = "decks";
+    const MAIN_DB_FILE_NAME: &'static str = "kanji_db.csv";
+    const EXTENDED_DB_FILE_NAME: &'static str = "kanji_db_extended.csv"; // Built from KANJIDIC2
+    pub const MAIN_DECK_NAME: &'static str = "All Kanji";
+    const MIN_SIZE: usize = 4; // Enough kanji for the answer options
+    const CSV_EXTENSION: &'static str = "csv";
+    const JSON_EXTENSION: &'static str = "json";
+
+    /// Decks found in the decks directory sorted by name
+    pub fn list() -> Vec<DeckInfo> {
+        let entries = match fs::read_dir(Self::dir()) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(), // No decks have been created
+        };
+        let mut decks = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let file_name = path.file_name()?.to_str()?.to_string();
+                let name = match path.extension()?.to_str()? {
+                    Self::CSV_EXTENSION => path.file_stem()?.to_str()?.to_string(),
+                    Self::JSON_EXTENSION => match Self::read_spec(&path) {
+                        Ok(spec) => spec.name,
+                        Err(err) => {
+                            eprintln!("Could not read deck {}: {}", file_name, err);
+                            return None;
+                        }
+                    },
+                    _ => return None,
+                };
+                Some(DeckInfo { file_name, name })
+            })
+            .collect::<Vec<DeckInfo>>();
+        decks.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        decks
+    }
+
+    /// Load the kanji of a deck file, or the main kanji database if no deck is given
+    pub fn load(file_name: Option<&str>) -> Result<Vec<KanjiRecord>, Box<dyn Error>> {
+        let file_name = match file_name {
+            Some(file_name) => file_name,
+            None => return KanjiRecord::from_csv_lenient(&Self::main_db_path()),
+        };
+        let path = Self::path(file_name);
+        let kanjis = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(Self::CSV_EXTENSION) => KanjiRecord::from_csv_lenient(&path)?,
+            Some(Self::JSON_EXTENSION) => {
+                let spec = Self::read_spec(&path)?;
+                KanjiRecord::from_csv_lenient(&Self::main_db_path())?
+                    .into_iter()
+                    .filter(|record| spec.kanji.contains(record.kanji))
+                    .collect()
+            }
+            _ => return Err(format!("Unknown deck file type: {}", file_name).into()),
+        };
+        if kanjis.len() < Self::MIN_SIZE {
+            return Err(
+                format!("Deck {} needs at least {} kanji", file_name, Self::MIN_SIZE).into(),
+            );
+        }
+        Ok(kanjis)
+    }
+
+    /// The extended kanji database if it has been built, otherwise the bundled database
+    pub fn main_db_path() -> PathBuf {
+        let extended_path = Self::extended_db_path();
+        if extended_path.exists() {
+            extended_path
+        } else {
+            Self::bundled_db_path()
+        }
+    }
+
+    #[inline]
+    pub fn bundled_db_path() -> PathBuf {
+        Storage::resource_dir().join(Self::MAIN_DB_FILE_NAME)
+    }
+
+    #[inline]
+    pub fn extended_db_path() -> PathBuf {
+        Storage::resource_dir().join(Self::EXTENDED_DB_FILE_NAME)
+    }
+
+    /// Directory of the custom decks in the data directory
+    #[inline]
+    pub fn dir() -> PathBuf {
+        Storage::data_dir().join(Self::DIR_NAME)
+    }
+
+    /// Save a JSON deck of kanji from the main database in the decks directory
+    pub fn create(name: &str, kanji: &str) -> Result<DeckInfo, Box<dyn Error>> {
+        if kanji.chars().count() < Self::MIN_SIZE {
+            return Err(format!("Deck {} needs at least {} kanji", name, Self::MIN_SIZE).into());
+        }
+        let file_stem = name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        let file_name = format!("{}.{}", file_stem, Self::JSON_EXTENSION);
+        let path = Self::path(&file_name);
+        if path.exists() {
+            return Err(format!("Deck file {} already exists", path.display()).into());
+        }
+        fs::create_dir_all(Self::dir())?;
+        let spec = DeckSpec {
+            name: name.to_string(),
+            kanji: kanji.to_string(),
+        };
+        serde_json::to_writer_pretty(BufWriter::new(File::create(&path)?), &spec)?;
+        Ok(DeckInfo {
+            file_name,
+            name: spec.name,
+        })
+    }
+
+    fn read_spec(path: &Path) -> Result<DeckSpec, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    #[inline]
+    fn path(file_name: &str) -> PathBuf {
+        Self::dir().join(file_name)
+    }
+}
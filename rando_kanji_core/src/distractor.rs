@@ -0,0 +1,269 @@
+use std::{collections::HashSet, fmt::Display};
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::ButtonTextOption, kanji::KanjiRecord};
+
+/// How the wrong answer options are picked for a multiple choice question
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum DistractorStrategy {
+    #[default]
+    Random, // Kanji from the current pool or anywhere in the deck
+    SimilarReading, // Kanji sharing on'yomi or kun'yomi with the correct kanji
+    SimilarMeaning, // Kanji with overlapping meaning keywords
+    SameJlpt,       // Kanji of the same JLPT level
+}
+
+impl DistractorStrategy {
+    const CLOSEST_MATCHES_FACTOR: usize = 2; // Variety among the best scoring options
+    const MIN_KEYWORD_LENGTH: usize = 3;
+    const STOP_WORDS: [&'static str; 8] = [
+        "the",
+        "and",
+        "for",
+        "with",
+        "off",
+        "out",
+        "someone",
+        "something",
+    ];
+
+    /// Pick up to `count` wrong options for the correct kanji.
+    /// Options the strategy can't provide are filled at random from the deck.
+    /// Options with the same text as another option are skipped so that every option is distinct
+    pub fn choose<'a>(
+        self,
+        correct: &KanjiRecord,
+        deck: &[&'a KanjiRecord],
+        pool: &[&'a KanjiRecord],
+        text_option: ButtonTextOption,
+        count: usize,
+    ) -> Vec<&'a KanjiRecord> {
+        let mut rng = rand::thread_rng();
+        let mut distractors = Distractors::new(correct, text_option, count);
+
+        let mut preferred = match self {
+            DistractorStrategy::Random => pool.to_vec(),
+            DistractorStrategy::SimilarReading => {
+                let readings = Self::readings(correct);
+                Self::closest_matches(deck, count, |record| {
+                    Self::readings(record).intersection(&readings).count()
+                })
+            }
+            DistractorStrategy::SimilarMeaning => {
+                let keywords = Self::meaning_keywords(correct);
+                Self::closest_matches(deck, count, |record| {
+                    Self::meaning_keywords(record)
+                        .intersection(&keywords)
+                        .count()
+                })
+            }
+            DistractorStrategy::SameJlpt => deck
+                .iter()
+                .filter(|record| record.jlpt == correct.jlpt)
+                .copied()
+                .collect(),
+        };
+        preferred.shuffle(&mut rng);
+        distractors.add_from(preferred);
+
+        if !distractors.is_full() {
+            let mut rest = deck.to_vec();
+            rest.shuffle(&mut rng);
+            distractors.add_from(rest);
+        }
+        distractors.records
+    }
+
+    /// The best scoring kanji in random order, kanji without any similarity are left out
+    fn closest_matches<'a>(
+        deck: &[&'a KanjiRecord],
+        count: usize,
+        score: impl Fn(&KanjiRecord) -> usize,
+    ) -> Vec<&'a KanjiRecord> {
+        let mut scored = deck
+            .iter()
+            .map(|record| (score(record), *record))
+            .filter(|(score, _)| *score > 0)
+            .collect::<Vec<(usize, &KanjiRecord)>>();
+        // Shuffle first so that kanji with equal scores are in random order after the stable sort
+        scored.shuffle(&mut rand::thread_rng());
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored
+            .into_iter()
+            .take(count * Self::CLOSEST_MATCHES_FACTOR)
+            .map(|(_, record)| record)
+            .collect()
+    }
+
+    /// On'yomi and kun'yomi in rōmaji, kun'yomi without okurigana
+    fn readings(record: &KanjiRecord) -> HashSet<String> {
+        let readings = record.readings();
+        readings
+            .on_romaji
+            .into_iter()
+            .chain(readings.kun_romaji)
+            .map(|reading| reading.stem)
+            .collect()
+    }
+
+    fn meaning_keywords(record: &KanjiRecord) -> HashSet<String> {
+        record
+            .on_trans
+            .split(|c: char| !c.is_alphabetic())
+            .chain(record.kun_trans.split(|c: char| !c.is_alphabetic()))
+            .map(|word| word.to_lowercase())
+            .filter(|word| {
+                word.len() >= Self::MIN_KEYWORD_LENGTH && !Self::STOP_WORDS.contains(&word.as_str())
+            })
+            .collect()
+    }
+}
+
+impl Display for DistractorStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DistractorStrategy::Random => write!(f, "Random"),
+            DistractorStrategy::SimilarReading => write!(f, "Similar reading"),
+            DistractorStrategy::SimilarMeaning => write!(f, "Similar meaning"),
+            DistractorStrategy::SameJlpt => write!(f, "Same JLPT level"),
+        }
+    }
+}
+
+/// Wrong answer options collected so far
+struct Distractors<'a> {
+    records: Vec<&'a KanjiRecord>,
+    kanji: HashSet<char>,
+    option_texts: HashSet<String>,
+    text_option: ButtonTextOption,
+    count: usize,
+}
+
+impl<'a> Distractors<'a> {
+    fn new(correct: &KanjiRecord, text_option: ButtonTextOption, count: usize) -> Self {
+        Self {
+            records: Vec::with_capacity(count),
+            kanji: HashSet::from([correct.kanji]),
+            option_texts: HashSet::from([correct.as_option_text(text_option)]),
+            text_option,
+            count,
+        }
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.records.len() >= self.count
+    }
+
+    fn add_from(&mut self, records: Vec<&'a KanjiRecord>) {
+        for record in records {
+            if self.is_full() {
+                return;
+            }
+            if self.kanji.contains(&record.kanji) {
+                continue;
+            }
+            let option_text = record.as_option_text(self.text_option);
+            if self.option_texts.insert(option_text) {
+                self.kanji.insert(record.kanji);
+                self.records.push(record);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Kanji of JLPT level 1 and 2, where 哀 and 愛 share the reading "ai"
+    /// and 愛 and 恋 share the meaning "love"
+    const ROWS: [&str; 6] = [
+        "1;亜;1;ア;;a;rank next, Asia;-;-",
+        "2;哀;1;アイ;;ai;pity, sorrow;-;-",
+        "3;愛;2;アイ;;ai;love, affection;-;-",
+        "4;恋;2;レン;;ren;romance, love;-;-",
+        "5;悪;2;アク;;aku;bad, evil;-;-",
+        "6;握;1;アク;;aku;grip, hold;-;-",
+    ];
+
+    fn deck() -> Vec<KanjiRecord> {
+        ROWS.iter().map(|row| KanjiRecord::from_row(row)).collect()
+    }
+
+    fn choose(
+        strategy: DistractorStrategy,
+        deck: &[KanjiRecord],
+        correct: char,
+        text_option: ButtonTextOption,
+        count: usize,
+    ) -> Vec<char> {
+        let correct = deck.iter().find(|record| record.kanji == correct).unwrap();
+        let deck = deck.iter().collect::<Vec<&KanjiRecord>>();
+        strategy
+            .choose(correct, &deck, &[], text_option, count)
+            .iter()
+            .map(|record| record.kanji)
+            .collect()
+    }
+
+    #[test]
+    fn choose_prefers_the_options_of_the_strategy() {
+        let deck = deck();
+        for _ in 0..10 {
+            let kanji = choose(
+                DistractorStrategy::SimilarReading,
+                &deck,
+                '哀',
+                ButtonTextOption::Meaning,
+                1,
+            );
+            assert_eq!(kanji, ['愛']);
+            let kanji = choose(
+                DistractorStrategy::SimilarMeaning,
+                &deck,
+                '愛',
+                ButtonTextOption::Kana,
+                1,
+            );
+            assert_eq!(kanji, ['恋']);
+            let mut kanji = choose(
+                DistractorStrategy::SameJlpt,
+                &deck,
+                '亜',
+                ButtonTextOption::Meaning,
+                2,
+            );
+            kanji.sort_unstable();
+            assert_eq!(kanji, ['哀', '握']);
+        }
+    }
+
+    #[test]
+    fn choose_fills_up_from_the_deck_with_distinct_options() {
+        let deck = deck();
+        for strategy in [
+            DistractorStrategy::Random,
+            DistractorStrategy::SimilarReading,
+            DistractorStrategy::SimilarMeaning,
+            DistractorStrategy::SameJlpt,
+        ] {
+            let kanji = choose(strategy, &deck, '亜', ButtonTextOption::Meaning, 5);
+            assert_eq!(kanji.len(), 5, "{}", strategy);
+            assert!(!kanji.contains(&'亜'), "{}", strategy);
+
+            // 哀 and 愛 read アイ, 悪 and 握 read アク, so only one of each can be an option
+            let kanji = choose(strategy, &deck, '亜', ButtonTextOption::Kana, 5);
+            assert_eq!(kanji.len(), 3, "{}", strategy);
+            let texts = kanji
+                .iter()
+                .map(|kanji| deck.iter().find(|record| record.kanji == *kanji).unwrap())
+                .map(|record| record.as_option_text(ButtonTextOption::Kana))
+                .collect::<HashSet<String>>();
+            assert_eq!(texts.len(), 3, "{}", strategy);
+        }
+    }
+}
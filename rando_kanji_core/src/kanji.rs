@@ -0,0 +1,373 @@
+use std::{error::Error, path::Path, time::SystemTime};
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{ButtonTextOption, Config, StatValue},
+    deck::Deck,
+    reading::KanjiReadings,
+    romaji,
+    validation::{Severity, Validation},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KanjiRecord {
+    pub id: u32,
+    pub kanji: char,
+    pub jlpt: u8,
+    pub joyo_reading: String,
+    pub reading: String,
+    #[serde(rename = "on")]
+    pub on_reading: String,
+    pub on_trans: String,
+    #[serde(rename = "kun")]
+    pub kun_reading: String,
+    pub kun_trans: String,
+    // Attributes of the extended database built from KANJIDIC2
+    #[serde(default)]
+    pub strokes: Option<u8>,
+    #[serde(default)]
+    pub grade: Option<u8>, // School grade 1 to 6, 8 for other jōyō kanji and 9 or 10 for jinmeiyō kanji
+    #[serde(default)]
+    pub frequency: Option<u16>, // Rank among the 2500 most used kanji in newspapers
+    #[serde(default)]
+    pub radical: Option<u8>, // Number of the classical Kangxi radical
+    #[serde(default)]
+    pub nanori: String, // Readings used in names, separated by "、"
+    #[serde(default)]
+    pub fr_trans: String,
+    #[serde(default)]
+    pub es_trans: String,
+    #[serde(default)]
+    pub pt_trans: String,
+}
+
+impl KanjiRecord {
+    const MAX_STRING_LENGTH: usize = 60; // Characters of an answer option
+    const MAX_MEANINGS: usize = 3;
+    pub(crate) const DELIMITER: u8 = b';';
+
+    pub fn from_csv(path: &Path) -> Result<Vec<KanjiRecord>, csv::Error> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(Self::DELIMITER)
+            .from_path(path)?;
+        reader
+            .deserialize()
+            .collect::<Result<Vec<KanjiRecord>, csv::Error>>()
+    }
+
+    /// Load the valid rows of a kanji database, skipping the rows with errors
+    pub fn from_csv_lenient(path: &Path) -> Result<Vec<KanjiRecord>, Box<dyn Error>> {
+        let validation = Validation::check(path)?;
+        for problem in validation.problems.iter() {
+            if problem.severity == Severity::Error {
+                eprintln!("Skipping row of {}, {}", path.display(), problem);
+            }
+        }
+        Ok(validation.kanjis)
+    }
+
+    pub fn to_csv(path: &Path, kanjis: &[KanjiRecord]) -> Result<(), csv::Error> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(Self::DELIMITER)
+            .from_path(path)?;
+        for record in kanjis {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn as_option_text(&self, text_option: ButtonTextOption) -> String {
+        match text_option {
+            ButtonTextOption::Kana => self.joyo_reading.trim().to_string(),
+            ButtonTextOption::Romaji => self.as_romaji(),
+            ButtonTextOption::Meaning => self.as_meaning(),
+        }
+    }
+
+    pub fn as_romaji(&self) -> String {
+        let readings = self.readings();
+        readings
+            .on_romaji
+            .iter()
+            .chain(readings.kun_romaji.iter())
+            .map(|reading| reading.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// The first meanings of the kanji that fit in the maximum length of an answer option
+    pub fn as_meaning(&self) -> String {
+        let mut built_string = String::new();
+        for meaning in self.readings().meanings.iter().take(Self::MAX_MEANINGS) {
+            let length = built_string.chars().count() + meaning.chars().count();
+            if !built_string.is_empty() {
+                if length + 2 > Self::MAX_STRING_LENGTH {
+                    break;
+                }
+                built_string += ", ";
+            }
+            built_string += meaning;
+        }
+        // A single meaning longer than the limit is cut at a character boundary
+        if built_string.chars().count() > Self::MAX_STRING_LENGTH {
+            built_string = built_string
+                .chars()
+                .take(Self::MAX_STRING_LENGTH - 3)
+                .collect::<String>()
+                + "...";
+        }
+        built_string
+    }
+
+    /// Readings and meanings parsed from the text fields
+    #[inline]
+    pub fn readings(&self) -> KanjiReadings {
+        KanjiReadings::parse(self)
+    }
+
+    pub fn is_correct_reading(&self, answer: &str) -> bool {
+        let answer = romaji::katakana_to_hiragana(answer.trim());
+        !answer.is_empty() && self.readings().accepted_readings().contains(&answer)
+    }
+}
+
+impl PartialEq for KanjiRecord {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+pub struct KanjiDealer {
+    pub kanjis: Vec<KanjiRecord>,
+    pub kanji_pool: Vec<char>, // Vector of kanji chars
+    last_dealt_kanji: char,
+    deck: Option<String>, // File name of the loaded custom deck
+}
+
+impl KanjiDealer {
+    const ANSWER_OPTION_COUNT: usize = 4;
+
+    pub fn new(config: &Config) -> Self {
+        let mut dealer = Self {
+            kanjis: Vec::new(),
+            kanji_pool: Vec::<char>::new(),
+            last_dealt_kanji: '-',
+            deck: None,
+        };
+        dealer.load_deck(config);
+        dealer
+    }
+
+    /// Load the deck selected in the profile, falling back to the main kanji database
+    pub fn load_deck(&mut self, config: &Config) {
+        self.kanjis = match Deck::load(config.deck.as_deref()) {
+            Ok(kanjis) => kanjis,
+            Err(err) => {
+                eprintln!("Could not load deck: {}", err);
+                Deck::load(None).expect("Could not load kanjis")
+            }
+        };
+        self.kanji_pool.clear();
+        self.deck = config.deck.clone();
+    }
+
+    /// Load the deck of the profile if another deck or profile has been chosen since it was loaded
+    pub fn sync_deck(&mut self, config: &Config) {
+        if self.deck != config.deck {
+            self.load_deck(config);
+        }
+    }
+
+    /// Add kanji to the pool for spaced learning.
+    /// Kanji stay in the pool while they are being learned or are due for a review
+    /// according to the scheduler. A new pool cycle starts when the pool runs empty
+    pub fn update_kanji_pool(&mut self, config: &mut Config) {
+        self.sync_deck(config);
+        let now = SystemTime::now();
+        // Drop kanji that have been learned and are not waiting for a review
+        self.kanji_pool
+            .retain(|kanji| match config.statistics().get(kanji) {
+                Some(stat) => stat.is_learning() || stat.is_due(now),
+                None => true, // Not answered yet
+            });
+        if self.kanji_pool.is_empty() {
+            self.fill_kanji_pool(config, now);
+        }
+    }
+
+    /// Deal a kanji with answer options. None if the pool is empty because every kanji has been suspended
+    pub fn deal_kanji_candidates(&mut self, config: &Config) -> Option<(u8, Vec<&KanjiRecord>)> {
+        let correct_answer = self.deal_kanji(config)?;
+        let correct_record = self.find_record_by_char(&correct_answer);
+        let deck = self.filtered_kanjis(config);
+        let pool = self
+            .kanji_pool
+            .iter()
+            .map(|kanji| self.find_record_by_char(kanji))
+            .collect::<Vec<&KanjiRecord>>();
+
+        let mut candidates = config.distractor_strategy.choose(
+            correct_record,
+            &deck,
+            &pool,
+            config.button_text_option,
+            Self::ANSWER_OPTION_COUNT - 1,
+        );
+        // Add correct answer option
+        let correct_index = rand::thread_rng().gen_range(0..=candidates.len());
+        candidates.insert(correct_index, correct_record);
+        Some((correct_index as u8, candidates))
+    }
+
+    /// Deal a single kanji without answer options
+    pub fn deal_kanji_record(&mut self, config: &Config) -> Option<&KanjiRecord> {
+        let kanji = self.deal_kanji(config)?;
+        Some(self.find_record_by_char(&kanji))
+    }
+
+    fn deal_kanji(&mut self, config: &Config) -> Option<char> {
+        // Make sure the next kanji won't be the same as last
+        let pool_char = config.scheduler.get().next_kanji(
+            &self.kanji_pool,
+            config.statistics(),
+            self.last_dealt_kanji,
+        )?;
+
+        self.last_dealt_kanji = self
+            .kanjis
+            .iter()
+            .find(|record| record.kanji == pool_char)
+            .map(|record| record.kanji)
+            .expect("Could not find record from kanji pool");
+
+        Some(self.last_dealt_kanji)
+    }
+
+    fn fill_kanji_pool(&mut self, config: &Config, now: SystemTime) {
+        let max_size = config.kanji_pool_max_size as usize;
+        // Due reviews first, the most overdue ones at the front
+        let mut due_kanji = config
+            .statistics()
+            .iter()
+            .filter(|(kanji, stat)| stat.is_due(now) && self.is_dealable(kanji, config))
+            .map(|(kanji, stat)| (*kanji, stat))
+            .collect::<Vec<(char, &StatValue)>>();
+        config.scheduler.get().sort_due_kanji(&mut due_kanji, now);
+        self.kanji_pool
+            .extend(due_kanji.iter().map(|(kanji, _)| *kanji).take(max_size));
+        // Then kanji that are still being learned
+        for (kanji, stat) in config.statistics().iter() {
+            if self.kanji_pool.len() >= max_size {
+                return;
+            }
+            if stat.is_learning() && self.is_dealable(kanji, config) {
+                self.kanji_pool.push(*kanji);
+            }
+        }
+        self.add_new_kanji_to_pool(config);
+        // Every kanji of the deck has been learned, review the next ones ahead of schedule
+        if self.kanji_pool.is_empty() {
+            let mut upcoming_kanji = config
+                .statistics()
+                .iter()
+                .filter(|(kanji, _)| self.is_dealable(kanji, config))
+                .collect::<Vec<(&char, &StatValue)>>();
+            upcoming_kanji.sort_unstable_by_key(|(_, stat)| stat.due_time);
+            self.kanji_pool.extend(
+                upcoming_kanji
+                    .iter()
+                    .map(|(kanji, _)| **kanji)
+                    .take(max_size),
+            );
+        }
+    }
+
+    fn add_new_kanji_to_pool(&mut self, config: &Config) {
+        let mut new_kanji = self
+            .filtered_kanjis(config)
+            .iter()
+            .map(|record| record.kanji)
+            // Suspended kanji are left out also when the filter falls back to all kanji
+            .filter(|kanji| {
+                !config.statistics().contains_key(kanji) && !config.deck_filter.excludes(*kanji)
+            })
+            .collect::<Vec<char>>();
+        new_kanji.shuffle(&mut rand::thread_rng());
+        let free_space =
+            (config.kanji_pool_max_size as usize).saturating_sub(self.kanji_pool.len());
+        self.kanji_pool
+            .extend(new_kanji.into_iter().take(free_space));
+    }
+
+    /// Kanji allowed by the deck filter of the profile.
+    /// Falls back to all kanji if the filter leaves too few for the answer options
+    fn filtered_kanjis(&self, config: &Config) -> Vec<&KanjiRecord> {
+        let filtered = self
+            .kanjis
+            .iter()
+            .filter(|record| config.deck_filter.includes(record))
+            .collect::<Vec<&KanjiRecord>>();
+        if filtered.len() < Self::ANSWER_OPTION_COUNT {
+            self.kanjis.iter().collect()
+        } else {
+            filtered
+        }
+    }
+
+    /// Start learning a kanji with the next questions, even if the deck filter would not deal it yet
+    pub fn add_to_pool(&mut self, kanji: char, config: &mut Config) {
+        config.deck_filter.excluded_kanji.retain(|c| c != kanji);
+        config.statistics_mut().entry(kanji).or_default();
+        if self.contains(&kanji) && !self.kanji_pool.contains(&kanji) {
+            self.kanji_pool.push(kanji);
+        }
+    }
+
+    /// Stop dealing a kanji until it is added again. Its statistics are kept
+    pub fn suspend(&mut self, kanji: char, config: &mut Config) {
+        if !config.deck_filter.excludes(kanji) {
+            config.deck_filter.excluded_kanji.push(kanji);
+        }
+        self.kanji_pool.retain(|c| *c != kanji);
+    }
+
+    /// Statistics may contain kanji of other decks
+    #[inline]
+    pub fn contains(&self, kanji: &char) -> bool {
+        self.kanjis.iter().any(|record| record.kanji == *kanji)
+    }
+
+    /// Reviewed kanji of the deck that haven't been suspended
+    #[inline]
+    fn is_dealable(&self, kanji: &char, config: &Config) -> bool {
+        self.contains(kanji) && !config.deck_filter.excludes(*kanji)
+    }
+
+    pub fn find_record_by_char(&self, char: &char) -> &KanjiRecord {
+        self.kanjis
+            .iter()
+            .find(|record| record.kanji == *char)
+            .expect("Could not find record by char")
+    }
+}
+
+#[cfg(test)]
+impl KanjiRecord {
+    /// Parse a row in the format of the main kanji database
+    pub(crate) fn from_row(row: &str) -> Self {
+        let text = format!(
+            "id;kanji;jlpt;joyo_reading;reading;on;on_trans;kun;kun_trans\n{}",
+            row
+        );
+        csv::ReaderBuilder::new()
+            .delimiter(Self::DELIMITER)
+            .from_reader(text.as_bytes())
+            .deserialize()
+            .next()
+            .expect("No row")
+            .expect("Invalid row")
+    }
+}
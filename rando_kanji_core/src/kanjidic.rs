@@ -0,0 +1,193 @@
+use std::{collections::HashMap, error::Error, fs::File, io::BufReader, mem, path::Path};
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::kanji::KanjiRecord;
+
+/// Attributes of a kanji in KANJIDIC2
+#[derive(Default)]
+struct KanjidicEntry {
+    strokes: Option<u8>,
+    grade: Option<u8>,
+    frequency: Option<u16>,
+    radical: Option<u8>,
+    nanori: Vec<String>,
+    meanings: HashMap<String, Vec<String>>, // Meanings by language code, English meanings are not kept
+}
+
+/// Element of a KANJIDIC2 character whose text is read
+enum Field {
+    Literal,
+    Radical,
+    Grade,
+    Strokes,
+    Frequency,
+    Nanori,
+    Meaning(String),
+}
+
+/// Reader of the KANJIDIC2 XML file of the Electronic Dictionary Research and Development Group
+/// (http://www.edrdg.org/wiki/index.php/KANJIDIC_Project)
+pub struct Kanjidic;
+
+impl Kanjidic {
+    /// Add the attributes of KANJIDIC2 to the kanji records. Returns the number of kanji found in it
+    pub fn extend_records(
+        path: &Path,
+        kanjis: &mut [KanjiRecord],
+    ) -> Result<usize, Box<dyn Error>> {
+        let mut entries = Self::read(path)?;
+        let mut found = 0;
+        for record in kanjis.iter_mut() {
+            let mut entry = match entries.remove(&record.kanji) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            found += 1;
+            record.strokes = entry.strokes;
+            record.grade = entry.grade;
+            record.frequency = entry.frequency;
+            record.radical = entry.radical;
+            record.nanori = entry.nanori.join("、");
+            let mut meanings =
+                |lang: &str| entry.meanings.remove(lang).unwrap_or_default().join(", ");
+            record.fr_trans = meanings("fr");
+            record.es_trans = meanings("es");
+            record.pt_trans = meanings("pt");
+        }
+        Ok(found)
+    }
+
+    fn read(path: &Path) -> Result<HashMap<char, KanjidicEntry>, Box<dyn Error>> {
+        let mut reader = Reader::from_reader(BufReader::new(File::open(path)?));
+        reader.config_mut().trim_text(true);
+        let mut entries = HashMap::new();
+        let mut entry = KanjidicEntry::default();
+        let mut literal = None;
+        let mut field = None;
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(element) => {
+                    field = match element.name().as_ref() {
+                        b"literal" => Some(Field::Literal),
+                        b"rad_value"
+                            if Self::attribute(&element, b"rad_type")?.as_deref()
+                                == Some("classical") =>
+                        {
+                            Some(Field::Radical)
+                        }
+                        b"grade" => Some(Field::Grade),
+                        b"stroke_count" => Some(Field::Strokes),
+                        b"freq" => Some(Field::Frequency),
+                        b"nanori" => Some(Field::Nanori),
+                        b"meaning" => Self::attribute(&element, b"m_lang")?.map(Field::Meaning),
+                        _ => None,
+                    };
+                }
+                Event::Text(text) => {
+                    let text = text.unescape()?;
+                    match &field {
+                        Some(Field::Literal) => literal = text.chars().next(),
+                        Some(Field::Radical) => entry.radical = text.parse().ok(),
+                        Some(Field::Grade) => entry.grade = text.parse().ok(),
+                        // The first stroke count is the accepted one, others are common miscounts
+                        Some(Field::Strokes) if entry.strokes.is_none() => {
+                            entry.strokes = text.parse().ok()
+                        }
+                        Some(Field::Frequency) => entry.frequency = text.parse().ok(),
+                        Some(Field::Nanori) => entry.nanori.push(text.into_owned()),
+                        Some(Field::Meaning(lang)) => entry
+                            .meanings
+                            .entry(lang.clone())
+                            .or_default()
+                            .push(text.into_owned()),
+                        _ => {}
+                    }
+                }
+                Event::End(element) => {
+                    field = None;
+                    if element.name().as_ref() == b"character" {
+                        let entry = mem::take(&mut entry);
+                        if let Some(kanji) = literal.take() {
+                            entries.insert(kanji, entry);
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(entries)
+    }
+
+    fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>, Box<dyn Error>> {
+        match element.try_get_attribute(name)? {
+            Some(attribute) => Ok(Some(attribute.unescape_value()?.into_owned())),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::storage::Storage;
+
+    const KANJIDIC: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kanjidic2>
+<header><file_version>4</file_version></header>
+<character>
+<literal>公</literal>
+<radical><rad_value rad_type="classical">12</rad_value><rad_value rad_type="nelson_c">28</rad_value></radical>
+<misc><grade>2</grade><stroke_count>4</stroke_count><stroke_count>5</stroke_count><freq>182</freq></misc>
+<reading_meaning>
+<rmgroup>
+<reading r_type="ja_on">コウ</reading>
+<meaning>public</meaning>
+<meaning m_lang="fr">public</meaning>
+<meaning m_lang="fr">prince</meaning>
+<meaning m_lang="es">p&#250;blico</meaning>
+</rmgroup>
+<nanori>きん</nanori>
+<nanori>ただ</nanori>
+</reading_meaning>
+</character>
+<character>
+<literal>龘</literal>
+<misc><stroke_count>48</stroke_count></misc>
+</character>
+</kanjidic2>
+"#;
+
+    #[test]
+    fn extend_records_adds_the_attributes_of_the_kanji_found() {
+        let path = Storage::use_test_data_dir().join("kanjidic2.xml");
+        Storage::create_parent_dir(&path).unwrap();
+        fs::write(&path, KANJIDIC).unwrap();
+        let mut kanjis = [
+            "589;公;2;コウ、おおやけ;ク、グ、きみ;koo;public;ooyake;public",
+            "1;亜;1;ア;;a;Asia;-;-",
+        ]
+        .map(KanjiRecord::from_row);
+
+        assert_eq!(Kanjidic::extend_records(&path, &mut kanjis).unwrap(), 1);
+        let kou = &kanjis[0];
+        assert_eq!(kou.radical, Some(12));
+        assert_eq!(kou.grade, Some(2));
+        assert_eq!(kou.strokes, Some(4));
+        assert_eq!(kou.frequency, Some(182));
+        assert_eq!(kou.nanori, "きん、ただ");
+        assert_eq!(kou.fr_trans, "public, prince");
+        assert_eq!(kou.es_trans, "público");
+        assert_eq!(kou.pt_trans, "");
+        assert_eq!(kanjis[1].strokes, None);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,20 @@
+//! Quiz logic of rando kanji without any front-end dependencies:
+//! kanji decks, review scheduling, profiles and quiz sessions
+
+pub mod anki;
+pub mod archive;
+pub mod browse;
+pub mod config;
+pub mod deck;
+pub mod distractor;
+pub mod kanji;
+pub mod kanjidic;
+pub mod migration;
+pub mod reading;
+pub mod review_log;
+pub mod romaji;
+pub mod scheduler;
+pub mod session;
+pub mod statistics;
+pub mod storage;
+pub mod validation;
@@ -0,0 +1,115 @@
+use std::error::Error;
+
+use serde_json::{Map, Value};
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), Box<dyn Error>>;
+
+/// Upgrades profile files written by older versions to the current profile format
+pub struct ProfileMigration;
+
+impl ProfileMigration {
+    pub const CURRENT_VERSION: u32 = 1;
+    const VERSION_KEY: &'static str = "version";
+    // Indexed by the version a migration upgrades from
+    const MIGRATIONS: [Migration; Self::CURRENT_VERSION as usize] = [Self::numeric_profile_id];
+
+    /// Upgrade the JSON of a profile in place. Returns the version the profile was saved with
+    pub fn migrate(value: &mut Value) -> Result<u32, Box<dyn Error>> {
+        let profile = value
+            .as_object_mut()
+            .ok_or("Profile is not a JSON object")?;
+        let version = match profile.get(Self::VERSION_KEY) {
+            None => 0, // Profiles had no version before the first migration
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or_else(|| format!("Invalid profile version: {}", version))?,
+        };
+        if version > Self::CURRENT_VERSION {
+            return Err(format!(
+                "Profile version {} is newer than the supported version {}, please update the app",
+                version,
+                Self::CURRENT_VERSION
+            )
+            .into());
+        }
+
+        for migration in Self::MIGRATIONS.iter().skip(version as usize) {
+            migration(profile)?;
+        }
+        profile.insert(
+            Self::VERSION_KEY.to_string(),
+            Value::from(Self::CURRENT_VERSION),
+        );
+        Ok(version)
+    }
+
+    /// Version 1 replaced the three fixed profile slots named "Profile1" to "Profile3"
+    /// by numeric IDs and moved the last used flag to the profile index
+    fn numeric_profile_id(profile: &mut Map<String, Value>) -> Result<(), Box<dyn Error>> {
+        profile.remove("was_used_last");
+        let id = profile
+            .get_mut("profile")
+            .and_then(|profile| profile.get_mut("id"))
+            .ok_or("Profile has no ID")?;
+        if let Some(name) = id.as_str() {
+            let number = name
+                .strip_prefix("Profile")
+                .and_then(|number| number.parse::<u32>().ok())
+                .ok_or_else(|| format!("Invalid profile ID: {}", name))?;
+            *id = Value::from(number);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn migrate_upgrades_unversioned_profiles() {
+        let mut value = json!({
+            "profile": { "name": "Kanji", "id": "Profile2" },
+            "was_used_last": true,
+        });
+        assert_eq!(ProfileMigration::migrate(&mut value).unwrap(), 0);
+        assert_eq!(
+            value,
+            json!({
+                "profile": { "name": "Kanji", "id": 2 },
+                "version": ProfileMigration::CURRENT_VERSION,
+            })
+        );
+    }
+
+    #[test]
+    fn migrate_keeps_current_profiles() {
+        let profile = json!({
+            "profile": { "name": "Kanji", "id": 7 },
+            "version": ProfileMigration::CURRENT_VERSION,
+        });
+        let mut value = profile.clone();
+        assert_eq!(
+            ProfileMigration::migrate(&mut value).unwrap(),
+            ProfileMigration::CURRENT_VERSION
+        );
+        assert_eq!(value, profile);
+    }
+
+    #[test]
+    fn migrate_rejects_invalid_profiles() {
+        let newer_version = ProfileMigration::CURRENT_VERSION + 1;
+        for mut value in [
+            json!([]),
+            json!({ "profile": { "name": "Kanji" } }),
+            json!({ "profile": { "name": "Kanji", "id": "Main" } }),
+            json!({ "profile": { "name": "Kanji", "id": 1 }, "version": "one" }),
+            json!({ "profile": { "name": "Kanji", "id": 1 }, "version": newer_version }),
+        ] {
+            assert!(ProfileMigration::migrate(&mut value).is_err(), "{}", value);
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use std::{fmt::Display, mem};
+
+use crate::{kanji::KanjiRecord, romaji};
+
+/// A single reading of a kanji in kana or rōmaji
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reading {
+    pub stem: String,      // Part of the reading written with the kanji
+    pub okurigana: String, // Kana written after the kanji, empty for on'yomi
+    pub is_uncommon: bool, // Rarely used readings are in square brackets in the kanji database
+}
+
+impl Reading {
+    const DASHES: [char; 3] = ['-', '‐', '－']; // Mark prefixes and suffixes, or stand for no reading
+
+    /// The reading including its okurigana
+    #[inline]
+    pub fn full(&self) -> String {
+        self.stem.clone() + &self.okurigana
+    }
+
+    /// Parse a kana reading such as "あわ-れむ", with the okurigana after the dash
+    fn from_kana(text: &str) -> Option<Self> {
+        let (text, is_uncommon) = Self::strip_brackets(text)?;
+        let (stem, okurigana) = text.split_once(['-', '.']).unwrap_or((text, ""));
+        Some(Self {
+            stem: stem.to_string(),
+            okurigana: okurigana.to_string(),
+            is_uncommon,
+        })
+    }
+
+    /// Parse a rōmaji reading such as "awa(remu)", with the okurigana in parentheses or after a dash
+    fn from_romaji(text: &str) -> Option<Self> {
+        let (text, is_uncommon) = Self::strip_brackets(text)?;
+        let (stem, okurigana) = text.split_once(['(', '-']).unwrap_or((text, ""));
+        Some(Self {
+            stem: stem.trim().to_lowercase(),
+            okurigana: okurigana
+                .trim_matches(|c: char| c == ')' || c == '-' || c.is_whitespace())
+                .to_lowercase(),
+            is_uncommon,
+        })
+    }
+
+    /// Remove whitespace, the brackets of uncommon readings and the dashes marking prefixes and suffixes.
+    /// None for an empty reading or a dash placeholder
+    fn strip_brackets(text: &str) -> Option<(&str, bool)> {
+        let text = text.trim();
+        let is_uncommon = text.starts_with('[');
+        let text = text
+            .trim_matches(|c: char| c == '[' || c == ']' || c.is_whitespace())
+            .trim_matches(Self::DASHES);
+        if text.is_empty() {
+            None
+        } else {
+            Some((text, is_uncommon))
+        }
+    }
+}
+
+impl Display for Reading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reading = if self.okurigana.is_empty() {
+            self.stem.clone()
+        } else {
+            format!("{}({})", self.stem, self.okurigana)
+        };
+        if self.is_uncommon {
+            write!(f, "[{}]", reading)
+        } else {
+            write!(f, "{}", reading)
+        }
+    }
+}
+
+/// Readings and meanings of a kanji parsed from the text fields of the kanji database
+#[derive(Debug, Clone, Default)]
+pub struct KanjiReadings {
+    pub on: Vec<Reading>,  // On'yomi in katakana
+    pub kun: Vec<Reading>, // Kun'yomi in hiragana
+    pub on_romaji: Vec<Reading>,
+    pub kun_romaji: Vec<Reading>,
+    pub meanings: Vec<String>, // English meanings of the on'yomi and kun'yomi without duplicates
+}
+
+impl KanjiReadings {
+    pub fn parse(record: &KanjiRecord) -> Self {
+        let mut readings = Self::default();
+        // The jōyō readings list on'yomi in katakana and kun'yomi in hiragana, e.g. アイ、あわ-れむ
+        for reading in record
+            .joyo_reading
+            .split(['、', ','])
+            .filter_map(Reading::from_kana)
+        {
+            if reading.stem.chars().next().is_some_and(Self::is_katakana) {
+                readings.on.push(reading);
+            } else {
+                readings.kun.push(reading);
+            }
+        }
+        readings.on_romaji = record
+            .on_reading
+            .split(',')
+            .filter_map(Reading::from_romaji)
+            .collect();
+        readings.kun_romaji = record
+            .kun_reading
+            .split(',')
+            .filter_map(Reading::from_romaji)
+            .collect();
+        for meaning in
+            Self::split_meanings(&record.on_trans).chain(Self::split_meanings(&record.kun_trans))
+        {
+            if !readings.meanings.contains(&meaning) {
+                readings.meanings.push(meaning);
+            }
+        }
+        readings
+    }
+
+    /// The jōyō readings in hiragana as they are written, kun'yomi together with their okurigana.
+    /// On'yomi with a long o are also accepted as spelled in the rōmaji readings, e.g. "joo" for じょう
+    pub fn accepted_readings(&self) -> Vec<String> {
+        let mut readings = self
+            .on
+            .iter()
+            .chain(self.kun.iter())
+            .map(|reading| romaji::katakana_to_hiragana(&reading.full()))
+            .collect::<Vec<String>>();
+        let long_o_readings = self
+            .on
+            .iter()
+            .filter_map(|reading| {
+                Self::long_o_as_romaji(&romaji::katakana_to_hiragana(&reading.full()))
+            })
+            .collect::<Vec<String>>();
+        readings.extend(long_o_readings);
+        readings.sort_unstable();
+        readings.dedup();
+        readings
+    }
+
+    /// Split a list of meanings at commas, semicolons and sentence periods outside of parentheses,
+    /// e.g. "rank next, -ous (in acids, salts). Asia."
+    fn split_meanings(text: &str) -> impl Iterator<Item = String> {
+        let mut meanings = Vec::new();
+        let mut meaning = String::new();
+        let mut depth = 0;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            // Periods inside of a meaning such as "e.g" are not followed by whitespace
+            let is_period = c == '.'
+                && match chars.peek() {
+                    Some(next) => next.is_whitespace(),
+                    None => true,
+                };
+            if depth <= 0 && (c == ',' || c == ';' || is_period) {
+                meanings.push(mem::take(&mut meaning));
+            } else {
+                meaning.push(c);
+            }
+        }
+        meanings.push(meaning);
+        meanings
+            .into_iter()
+            .map(|meaning| meaning.trim().trim_end_matches('.').trim_end().to_string())
+            .filter(|meaning| !meaning.is_empty() && meaning != "-")
+    }
+
+    /// The rōmaji readings write a long o as "oo", which becomes おお instead of おう in kana.
+    /// None if the reading has no long o
+    fn long_o_as_romaji(reading: &str) -> Option<String> {
+        let mut previous = None;
+        let mut has_long_o = false;
+        let converted = reading
+            .chars()
+            .map(|c| {
+                let is_long_o = c == 'う' && previous.is_some_and(Self::is_o_row);
+                previous = Some(c);
+                has_long_o |= is_long_o;
+                if is_long_o {
+                    'お'
+                } else {
+                    c
+                }
+            })
+            .collect();
+        has_long_o.then_some(converted)
+    }
+
+    #[inline]
+    fn is_o_row(c: char) -> bool {
+        "おこごそぞとどのほぼぽもよょろをぉ".contains(c)
+    }
+
+    #[inline]
+    fn is_katakana(c: char) -> bool {
+        matches!(c, 'ァ'..='ヺ' | 'ー')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOU: &str = "1009;上;5;ジョウ、[ショウ]、うえ、[うわ]、かみ、あ-げる、あ-がる;;joo, [shoo];\"top; best; first volume. upper part; best\";ue, [uwa], kami, a(geru), a(garu);\"up, upper part, top. rise, go up.\"";
+    const A: &str = "1;亜;1;ア;;a;rank next, come after, Asia, sub-, -ous (in acids);-;-";
+    const KOU: &str = "589;公;2;コウ、おおやけ;ク、グ、きみ;koo;\"public, duke, lord; public\";ooyake;\"public, open; official\"";
+
+    fn readings(row: &str) -> KanjiReadings {
+        KanjiReadings::parse(&KanjiRecord::from_row(row))
+    }
+
+    #[test]
+    fn parse_splits_on_and_kun_readings() {
+        let readings = readings(JOU);
+        let on = readings
+            .on
+            .iter()
+            .map(Reading::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(on, ["ジョウ", "[ショウ]"]);
+        let kun = readings
+            .kun
+            .iter()
+            .map(Reading::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(kun, ["うえ", "[うわ]", "かみ", "あ(げる)", "あ(がる)"]);
+        assert_eq!(readings.kun[3].stem, "あ");
+        assert_eq!(readings.kun[3].okurigana, "げる");
+
+        let kun_romaji = readings
+            .kun_romaji
+            .iter()
+            .map(Reading::full)
+            .collect::<Vec<_>>();
+        assert_eq!(kun_romaji, ["ue", "uwa", "kami", "ageru", "agaru"]);
+        assert!(readings.on_romaji[1].is_uncommon);
+    }
+
+    #[test]
+    fn parse_splits_meanings_outside_of_parentheses() {
+        assert_eq!(
+            readings(JOU).meanings,
+            [
+                "top",
+                "best",
+                "first volume",
+                "upper part",
+                "up",
+                "rise",
+                "go up"
+            ]
+        );
+        assert_eq!(
+            readings(A).meanings,
+            ["rank next", "come after", "Asia", "sub-", "-ous (in acids)"]
+        );
+    }
+
+    #[test]
+    fn parse_drops_placeholder_readings() {
+        let readings = readings(A);
+        assert_eq!(readings.on.len(), 1);
+        assert!(readings.kun.is_empty());
+        assert!(readings.kun_romaji.is_empty());
+    }
+
+    #[test]
+    fn accepted_readings_are_full_jouyou_readings_in_hiragana() {
+        assert_eq!(
+            readings(JOU).accepted_readings(),
+            [
+                "あがる",
+                "あげる",
+                "うえ",
+                "うわ",
+                "かみ",
+                "しょう",
+                "しょお",
+                "じょう",
+                "じょお"
+            ]
+        );
+        // Readings outside of the jōyō readings are not accepted
+        assert_eq!(
+            readings(KOU).accepted_readings(),
+            ["おおやけ", "こう", "こお"]
+        );
+        assert_eq!(readings(A).accepted_readings(), ["あ"]);
+    }
+
+    #[test]
+    fn accepted_readings_include_the_long_o_of_the_romaji_readings() {
+        let jou = KanjiRecord::from_row(JOU);
+        for answer in ["joo", "jou", "shoo", "shou"] {
+            assert!(
+                jou.is_correct_reading(&romaji::to_hiragana(answer)),
+                "{}",
+                answer
+            );
+        }
+        let kou = KanjiRecord::from_row(KOU);
+        for answer in ["koo", "kou", "ooyake"] {
+            assert!(
+                kou.is_correct_reading(&romaji::to_hiragana(answer)),
+                "{}",
+                answer
+            );
+        }
+        // Only on'yomi are written with "oo", the kun'yomi おおやけ has no other spelling
+        assert!(!kou.is_correct_reading(&romaji::to_hiragana("ouyake")));
+        assert!(!jou.is_correct_reading(&romaji::to_hiragana("jo")));
+    }
+}
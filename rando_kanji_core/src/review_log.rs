@@ -0,0 +1,207 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs::{self, OpenOptions},
+    io::ErrorKind,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{ButtonTextOption, QuizMode, StatValue},
+    scheduler::Scheduler,
+    storage::Storage,
+};
+
+/// A single answer given by the learner
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReviewLogEntry {
+    pub timestamp: u64, // Seconds since the Unix epoch
+    pub kanji: char,
+    pub quiz_mode: QuizMode,
+    pub text_option: ButtonTextOption,
+    pub chosen_option: String,
+    pub correct_option: String,
+    pub is_correct: bool,
+    pub response_time_ms: u64,
+}
+
+impl ReviewLogEntry {
+    pub fn time(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(self.timestamp)
+    }
+}
+
+/// Append-only log of every answer, stored as a CSV file next to each profile
+pub struct ReviewLog;
+
+impl ReviewLog {
+    pub const FILE_EXTENSION: &'static str = "_reviews.csv";
+    const DELIMITER: u8 = b';';
+
+    pub fn append(path: &Path, entry: &ReviewLogEntry) -> Result<(), Box<dyn Error>> {
+        Storage::create_parent_dir(path)?;
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_new_file = file.metadata()?.len() == 0;
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(Self::DELIMITER)
+            .has_headers(is_new_file)
+            .from_writer(file);
+        writer.serialize(entry)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Vec<ReviewLogEntry>, Box<dyn Error>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(Self::DELIMITER)
+            .from_path(path)?;
+        let entries = reader
+            .deserialize()
+            .collect::<Result<Vec<ReviewLogEntry>, csv::Error>>()?;
+        Ok(entries)
+    }
+
+    /// Entries of the log, none if the profile has no log yet
+    pub fn read_or_empty(path: &Path) -> Result<Vec<ReviewLogEntry>, Box<dyn Error>> {
+        if path.exists() {
+            Self::read(path)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Replace the log with the given entries
+    pub fn write(path: &Path, entries: &[ReviewLogEntry]) -> Result<(), Box<dyn Error>> {
+        Storage::write_atomic(path, |file| {
+            let mut writer = csv::WriterBuilder::new()
+                .delimiter(Self::DELIMITER)
+                .from_writer(file);
+            for entry in entries {
+                writer.serialize(entry)?;
+            }
+            writer.flush()?;
+            Ok(())
+        })
+    }
+
+    /// Add the entries that aren't in the log yet, keeping the log in chronological order.
+    /// Returns the number of added entries
+    pub fn merge(entries: &mut Vec<ReviewLogEntry>, other: Vec<ReviewLogEntry>) -> usize {
+        // The response time tells apart identical answers given within the same second
+        let key = |entry: &ReviewLogEntry| {
+            (
+                entry.timestamp,
+                entry.kanji,
+                entry.quiz_mode,
+                entry.chosen_option.clone(),
+                entry.response_time_ms,
+            )
+        };
+        let mut seen = entries.iter().map(key).collect::<HashSet<_>>();
+        let count = entries.len();
+        entries.extend(other.into_iter().filter(|entry| seen.insert(key(entry))));
+        entries.sort_by_key(|entry| entry.timestamp); // Stable so answers within a second keep their order
+        entries.len() - count
+    }
+
+    pub fn remove(path: &Path) {
+        if let Err(err) = fs::remove_file(path) {
+            if err.kind() != ErrorKind::NotFound {
+                eprintln!("Error removing review log: {}", err);
+            }
+        }
+    }
+
+    /// Rebuild the answer statistics by replaying the logged answers with the given scheduler
+    pub fn replay<'a>(
+        entries: impl IntoIterator<Item = &'a ReviewLogEntry>,
+        scheduler: &dyn Scheduler,
+        learning_index_threshold: i32,
+    ) -> HashMap<char, StatValue> {
+        let mut statistics = HashMap::<char, StatValue>::new();
+        for entry in entries {
+            let stat = statistics.entry(entry.kanji).or_insert_with(|| StatValue {
+                last_review_time: entry.time(),
+                ..Default::default()
+            });
+            scheduler.record_answer(
+                stat,
+                entry.is_correct,
+                learning_index_threshold,
+                entry.time(),
+            );
+        }
+        statistics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: u64, kanji: char, chosen_option: &str) -> ReviewLogEntry {
+        ReviewLogEntry {
+            timestamp,
+            kanji,
+            quiz_mode: QuizMode::MultipleChoice,
+            text_option: ButtonTextOption::Kana,
+            chosen_option: chosen_option.to_string(),
+            correct_option: "ア".to_string(),
+            is_correct: chosen_option == "ア",
+            response_time_ms: 1000,
+        }
+    }
+
+    fn summary(entries: &[ReviewLogEntry]) -> Vec<(u64, char, &str)> {
+        entries
+            .iter()
+            .map(|entry| (entry.timestamp, entry.kanji, entry.chosen_option.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn merge_adds_missing_entries_in_chronological_order() {
+        let mut entries = vec![
+            entry(10, '亜', "イ"),
+            entry(10, '亜', "ア"),
+            entry(30, '哀', "ア"),
+        ];
+        let other = vec![
+            entry(10, '亜', "ア"),
+            entry(20, '愛', "ア"),
+            entry(40, '亜', "ア"),
+        ];
+        assert_eq!(ReviewLog::merge(&mut entries, other), 2);
+        assert_eq!(
+            summary(&entries),
+            [
+                (10, '亜', "イ"),
+                (10, '亜', "ア"),
+                (20, '愛', "ア"),
+                (30, '哀', "ア"),
+                (40, '亜', "ア"),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_keeps_identical_answers_within_the_same_second() {
+        let mut entries = vec![entry(10, '亜', "ア")];
+        let mut other = vec![entry(10, '亜', "ア"), entry(10, '亜', "ア")];
+        other[1].response_time_ms = 400;
+        assert_eq!(ReviewLog::merge(&mut entries, other), 1);
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn merge_skips_entries_that_are_already_logged() {
+        let mut entries = vec![entry(10, '亜', "ア"), entry(20, '愛', "ア")];
+        let other = entries.clone();
+        assert_eq!(ReviewLog::merge(&mut entries, other), 0);
+        assert_eq!(ReviewLog::merge(&mut entries, Vec::new()), 0);
+        assert_eq!(entries.len(), 2);
+    }
+}
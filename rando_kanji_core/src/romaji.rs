@@ -0,0 +1,198 @@
+/// Syllables ordered so that longer spellings are matched first
+#[rustfmt::skip]
+const SYLLABLES: &[(&str, &str)] = &[
+    // Three letters
+    ("kya", "きゃ"), ("kyu", "きゅ"), ("kyo", "きょ"),
+    ("gya", "ぎゃ"), ("gyu", "ぎゅ"), ("gyo", "ぎょ"),
+    ("sha", "しゃ"), ("shi", "し"), ("shu", "しゅ"), ("she", "しぇ"), ("sho", "しょ"),
+    ("sya", "しゃ"), ("syu", "しゅ"), ("syo", "しょ"),
+    ("jya", "じゃ"), ("jyu", "じゅ"), ("jyo", "じょ"),
+    ("zya", "じゃ"), ("zyu", "じゅ"), ("zyo", "じょ"),
+    ("cha", "ちゃ"), ("chi", "ち"), ("chu", "ちゅ"), ("che", "ちぇ"), ("cho", "ちょ"),
+    ("tya", "ちゃ"), ("tyu", "ちゅ"), ("tyo", "ちょ"),
+    ("dya", "ぢゃ"), ("dyu", "ぢゅ"), ("dyo", "ぢょ"),
+    ("tsu", "つ"),
+    ("nya", "にゃ"), ("nyu", "にゅ"), ("nyo", "にょ"),
+    ("hya", "ひゃ"), ("hyu", "ひゅ"), ("hyo", "ひょ"),
+    ("bya", "びゃ"), ("byu", "びゅ"), ("byo", "びょ"),
+    ("pya", "ぴゃ"), ("pyu", "ぴゅ"), ("pyo", "ぴょ"),
+    ("mya", "みゃ"), ("myu", "みゅ"), ("myo", "みょ"),
+    ("rya", "りゃ"), ("ryu", "りゅ"), ("ryo", "りょ"),
+    ("xtu", "っ"), ("ltu", "っ"),
+    ("xya", "ゃ"), ("xyu", "ゅ"), ("xyo", "ょ"),
+    ("lya", "ゃ"), ("lyu", "ゅ"), ("lyo", "ょ"),
+    // Two letters
+    ("ka", "か"), ("ki", "き"), ("ku", "く"), ("ke", "け"), ("ko", "こ"),
+    ("ga", "が"), ("gi", "ぎ"), ("gu", "ぐ"), ("ge", "げ"), ("go", "ご"),
+    ("sa", "さ"), ("si", "し"), ("su", "す"), ("se", "せ"), ("so", "そ"),
+    ("za", "ざ"), ("zi", "じ"), ("zu", "ず"), ("ze", "ぜ"), ("zo", "ぞ"),
+    ("ja", "じゃ"), ("ji", "じ"), ("ju", "じゅ"), ("je", "じぇ"), ("jo", "じょ"),
+    ("ta", "た"), ("ti", "ち"), ("tu", "つ"), ("te", "て"), ("to", "と"),
+    ("da", "だ"), ("di", "ぢ"), ("du", "づ"), ("de", "で"), ("do", "ど"),
+    ("na", "な"), ("ni", "に"), ("nu", "ぬ"), ("ne", "ね"), ("no", "の"),
+    ("ha", "は"), ("hi", "ひ"), ("hu", "ふ"), ("he", "へ"), ("ho", "ほ"),
+    ("fa", "ふぁ"), ("fi", "ふぃ"), ("fu", "ふ"), ("fe", "ふぇ"), ("fo", "ふぉ"),
+    ("ba", "ば"), ("bi", "び"), ("bu", "ぶ"), ("be", "べ"), ("bo", "ぼ"),
+    ("pa", "ぱ"), ("pi", "ぴ"), ("pu", "ぷ"), ("pe", "ぺ"), ("po", "ぽ"),
+    ("ma", "ま"), ("mi", "み"), ("mu", "む"), ("me", "め"), ("mo", "も"),
+    ("ya", "や"), ("yu", "ゆ"), ("yo", "よ"),
+    ("ra", "ら"), ("ri", "り"), ("ru", "る"), ("re", "れ"), ("ro", "ろ"),
+    ("la", "ら"), ("li", "り"), ("lu", "る"), ("le", "れ"), ("lo", "ろ"),
+    ("wa", "わ"), ("wi", "うぃ"), ("we", "うぇ"), ("wo", "を"),
+    ("va", "ゔぁ"), ("vi", "ゔぃ"), ("vu", "ゔ"), ("ve", "ゔぇ"), ("vo", "ゔぉ"),
+    ("xa", "ぁ"), ("xi", "ぃ"), ("xu", "ぅ"), ("xe", "ぇ"), ("xo", "ぉ"),
+    ("n'", "ん"),
+    // Single letters
+    ("a", "あ"), ("i", "い"), ("u", "う"), ("e", "え"), ("o", "お"),
+    ("-", "ー"),
+];
+
+const LONGEST_SYLLABLE: usize = 3;
+const KATAKANA_OFFSET: u32 = 'ア' as u32 - 'あ' as u32;
+
+/// Convert rōmaji to hiragana the same way as a Japanese input method would.
+/// Both Hepburn and Kunrei-shiki spellings are accepted.
+/// Characters that can't be converted are kept as they are
+pub fn to_hiragana(romaji: &str) -> String {
+    let chars = romaji.to_lowercase().chars().collect::<Vec<char>>();
+    let mut kana = String::with_capacity(romaji.len() * 3);
+    let mut i = 0;
+
+    while i < chars.len() {
+        let current = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        // Double consonants become a small tsu, e.g. "kitte" or "matcha"
+        if is_consonant(current)
+            && current != 'n'
+            && (next == Some(current) || (current == 't' && next == Some('c')))
+        {
+            kana.push('っ');
+            i += 1;
+            continue;
+        }
+
+        // Syllabic n before a consonant, at the end of input or written as "nn"
+        if current == 'n' {
+            match next {
+                None => {
+                    kana.push('ん');
+                    i += 1;
+                    continue;
+                }
+                Some('n') => {
+                    kana.push('ん');
+                    // Keep the second n if it starts a new syllable as in "onna"
+                    let after = chars.get(i + 2).copied();
+                    i += if matches!(after, Some(c) if is_vowel(c) || c == 'y') {
+                        1
+                    } else {
+                        2
+                    };
+                    continue;
+                }
+                Some(c) if !is_vowel(c) && c != 'y' && c != '\'' => {
+                    kana.push('ん');
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        let syllable = (1..=LONGEST_SYLLABLE.min(chars.len() - i))
+            .rev()
+            .find_map(|len| {
+                let candidate = chars[i..i + len].iter().collect::<String>();
+                SYLLABLES
+                    .iter()
+                    .find(|(romaji, _)| *romaji == candidate)
+                    .map(|(_, kana)| (len, *kana))
+            });
+        match syllable {
+            Some((len, syllable_kana)) => {
+                kana.push_str(syllable_kana);
+                i += len;
+            }
+            None => {
+                kana.push(current);
+                i += 1;
+            }
+        }
+    }
+    kana
+}
+
+pub fn hiragana_to_katakana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'ぁ'..='ゖ' => char::from_u32(c as u32 + KATAKANA_OFFSET).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+pub fn katakana_to_hiragana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'ァ'..='ヶ' => char::from_u32(c as u32 - KATAKANA_OFFSET).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+#[inline]
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+#[inline]
+fn is_consonant(c: char) -> bool {
+    c.is_ascii_alphabetic() && !is_vowel(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_hiragana_converts_hepburn_and_kunrei_spellings() {
+        assert_eq!(to_hiragana("kanji"), "かんじ");
+        assert_eq!(to_hiragana("shinbun"), "しんぶん");
+        assert_eq!(to_hiragana("sinbun"), "しんぶん");
+        assert_eq!(to_hiragana("tsuki"), "つき");
+        assert_eq!(to_hiragana("tuki"), "つき");
+        assert_eq!(to_hiragana("Kyou"), "きょう");
+        assert_eq!(to_hiragana("ra-men"), "らーめん");
+    }
+
+    #[test]
+    fn to_hiragana_converts_double_consonants_to_small_tsu() {
+        assert_eq!(to_hiragana("kitte"), "きって");
+        assert_eq!(to_hiragana("matcha"), "まっちゃ");
+        assert_eq!(to_hiragana("zasshi"), "ざっし");
+    }
+
+    #[test]
+    fn to_hiragana_converts_syllabic_n() {
+        assert_eq!(to_hiragana("hon"), "ほん");
+        assert_eq!(to_hiragana("onna"), "おんな");
+        assert_eq!(to_hiragana("kin'en"), "きんえん");
+        assert_eq!(to_hiragana("kinen"), "きねん");
+        assert_eq!(to_hiragana("konnnichiha"), "こんにちは");
+        assert_eq!(to_hiragana("hannya"), "はんにゃ");
+    }
+
+    #[test]
+    fn to_hiragana_keeps_characters_it_cannot_convert() {
+        assert_eq!(to_hiragana("ka?"), "か?");
+        assert_eq!(to_hiragana("かna"), "かな");
+        assert_eq!(to_hiragana(""), "");
+    }
+
+    #[test]
+    fn katakana_and_hiragana_convert_both_ways() {
+        assert_eq!(katakana_to_hiragana("ジョウ・ー"), "じょう・ー");
+        assert_eq!(hiragana_to_katakana("じょう"), "ジョウ");
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, SystemTime},
+};
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::config::StatValue;
+
+pub use self::{fsrs::FsrsScheduler, leitner::LeitnerScheduler, sm2::Sm2Scheduler};
+
+mod fsrs;
+mod leitner;
+mod sm2;
+
+/// Spaced repetition algorithm deciding when and in which order kanji are reviewed.
+/// New and lapsed kanji are first drilled in the kanji pool until their learning index
+/// reaches the learning threshold, after which they graduate to scheduled reviews
+pub trait Scheduler {
+    /// Update the algorithm specific state of a kanji after a scheduled review.
+    /// An incorrect answer is a lapse and must reset the review interval to zero
+    fn review(&self, stat: &mut StatValue, is_correct: bool, now: SystemTime);
+
+    /// Compute when the kanji should be reviewed next
+    fn due_time(&self, stat: &StatValue) -> SystemTime {
+        stat.last_review_time + stat.review_interval
+    }
+
+    /// Update the learning state of a kanji after an answer
+    fn record_answer(
+        &self,
+        stat: &mut StatValue,
+        is_correct: bool,
+        learning_index_threshold: i32,
+        now: SystemTime,
+    ) {
+        let is_review = if is_correct {
+            stat.learning_index += 1;
+            if stat.is_learning() {
+                // Graduate the kanji to scheduled reviews
+                stat.learning_index >= learning_index_threshold
+            } else {
+                stat.is_due(now)
+            }
+        } else if stat.is_learning() {
+            stat.learning_index -= 1;
+            false
+        } else {
+            // Lapse, the kanji has to be relearned with one correct answer
+            stat.learning_index = stat.learning_index.min(learning_index_threshold) - 1;
+            true
+        };
+
+        if is_review {
+            self.review(stat, is_correct, now);
+            stat.last_review_time = now;
+            stat.due_time = self.due_time(stat);
+        } else {
+            // Answers while learning count too, the latest progress wins when profiles are merged
+            stat.last_review_time = now;
+        }
+    }
+
+    /// Order the kanji that are due for a review, the most urgent first
+    fn sort_due_kanji(&self, due_kanji: &mut [(char, &StatValue)], _now: SystemTime) {
+        due_kanji.sort_unstable_by_key(|(_, stat)| stat.due_time);
+    }
+
+    /// Pick the next kanji to show from the kanji pool, avoiding the last shown kanji if possible
+    fn next_kanji(
+        &self,
+        kanji_pool: &[char],
+        _statistics: &HashMap<char, StatValue>,
+        last_kanji: char,
+    ) -> Option<char> {
+        if kanji_pool.len() == 1 {
+            return kanji_pool.first().copied();
+        }
+        kanji_pool
+            .iter()
+            .filter(|kanji| **kanji != last_kanji)
+            .copied()
+            .collect::<Vec<char>>()
+            .choose(&mut rand::thread_rng())
+            .copied()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum SchedulerKind {
+    #[default]
+    Sm2,
+    Leitner,
+    Fsrs,
+}
+
+impl SchedulerKind {
+    pub fn get(self) -> &'static dyn Scheduler {
+        match self {
+            SchedulerKind::Sm2 => &Sm2Scheduler,
+            SchedulerKind::Leitner => &LeitnerScheduler,
+            SchedulerKind::Fsrs => &FsrsScheduler,
+        }
+    }
+}
+
+impl Display for SchedulerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchedulerKind::Sm2 => write!(f, "SM-2"),
+            SchedulerKind::Leitner => write!(f, "Leitner boxes"),
+            SchedulerKind::Fsrs => write!(f, "FSRS"),
+        }
+    }
+}
+
+/// Overview of the scheduled reviews waiting for the learner
+#[derive(Clone, Copy, Default)]
+pub struct ReviewSummary {
+    pub overdue: usize,   // Reviews that are already due
+    pub due_today: usize, // Reviews due within the next day, including the overdue ones
+}
+
+impl ReviewSummary {
+    pub fn new(statistics: &HashMap<char, StatValue>, now: SystemTime) -> Self {
+        let tomorrow = now + days(1.0);
+        let mut summary = Self::default();
+        for stat in statistics.values().filter(|stat| !stat.is_learning()) {
+            if stat.due_time <= now {
+                summary.overdue += 1;
+            }
+            if stat.due_time <= tomorrow {
+                summary.due_today += 1;
+            }
+        }
+        summary
+    }
+}
+
+impl Display for ReviewSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Reviews due today: {} ・ Overdue: {}",
+            self.due_today, self.overdue
+        )
+    }
+}
+
+const fn days_to_seconds(days: u64) -> u64 {
+    days * 24 * 60 * 60
+}
+
+#[inline]
+fn days(days: f32) -> Duration {
+    Duration::from_secs_f32(days * days_to_seconds(1) as f32)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const THRESHOLD: i32 = 3;
+    const KINDS: [SchedulerKind; 3] = [
+        SchedulerKind::Sm2,
+        SchedulerKind::Leitner,
+        SchedulerKind::Fsrs,
+    ];
+
+    pub(crate) fn time(days: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(days_to_seconds(days))
+    }
+
+    /// A kanji answered correctly until it graduated to scheduled reviews on the given day
+    pub(crate) fn graduated(scheduler: &dyn Scheduler, day: u64) -> StatValue {
+        let mut stat = StatValue::default();
+        for _ in 0..THRESHOLD {
+            scheduler.record_answer(&mut stat, true, THRESHOLD, time(day));
+        }
+        stat
+    }
+
+    #[test]
+    fn answers_while_learning_do_not_schedule_reviews() {
+        for kind in KINDS {
+            let mut stat = StatValue::default();
+            for (day, is_correct) in [(0, true), (1, false), (2, true), (3, true)] {
+                kind.get()
+                    .record_answer(&mut stat, is_correct, THRESHOLD, time(day));
+            }
+            assert_eq!(stat.learning_index, 2, "{}", kind);
+            assert!(stat.is_learning(), "{}", kind);
+            assert_eq!(stat.repetitions, 0, "{}", kind);
+            assert_eq!(stat.last_review_time, time(3), "{}", kind);
+        }
+    }
+
+    #[test]
+    fn kanji_graduate_at_the_learning_threshold() {
+        for kind in KINDS {
+            let stat = graduated(kind.get(), 10);
+            assert!(!stat.is_learning(), "{}", kind);
+            assert_eq!(stat.repetitions, 1, "{}", kind);
+            assert_eq!(stat.last_review_time, time(10), "{}", kind);
+            assert_eq!(stat.due_time, time(10) + stat.review_interval, "{}", kind);
+        }
+    }
+
+    #[test]
+    fn correct_answers_before_the_due_time_keep_the_schedule() {
+        for kind in KINDS {
+            let mut stat = graduated(kind.get(), 0);
+            let due_time = stat.due_time;
+            kind.get()
+                .record_answer(&mut stat, true, THRESHOLD, time(0));
+            assert_eq!(stat.due_time, due_time, "{}", kind);
+            assert_eq!(stat.repetitions, 1, "{}", kind);
+        }
+    }
+
+    #[test]
+    fn lapsed_kanji_are_relearned_with_one_correct_answer() {
+        for kind in KINDS {
+            let scheduler = kind.get();
+            let mut stat = graduated(scheduler, 0);
+            scheduler.record_answer(&mut stat, false, THRESHOLD, time(1));
+            assert!(stat.is_learning(), "{}", kind);
+            assert_eq!(stat.lapses, 1, "{}", kind);
+            assert_eq!(stat.learning_index, THRESHOLD - 1, "{}", kind);
+
+            scheduler.record_answer(&mut stat, true, THRESHOLD, time(1));
+            assert!(!stat.is_learning(), "{}", kind);
+        }
+    }
+
+    #[test]
+    fn review_summary_counts_overdue_and_upcoming_reviews() {
+        let scheduler = SchedulerKind::Sm2.get();
+        let statistics = HashMap::from([
+            ('一', graduated(scheduler, 0)), // Due on day 1
+            ('二', graduated(scheduler, 1)), // Due on day 2
+            ('三', graduated(scheduler, 5)), // Due on day 6
+            ('四', StatValue::default()),    // Still learning
+        ]);
+        let summary = ReviewSummary::new(&statistics, time(1));
+        assert_eq!(summary.overdue, 1);
+        assert_eq!(summary.due_today, 2);
+    }
+}
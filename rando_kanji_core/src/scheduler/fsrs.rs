@@ -0,0 +1,156 @@
+use std::time::SystemTime;
+
+use super::{days, days_to_seconds, Scheduler};
+use crate::config::StatValue;
+
+/// Scheduler based on the Free Spaced Repetition Scheduler (FSRS v4.5) memory model.
+/// Each kanji has a stability (days until recall probability drops to 90 %)
+/// and a difficulty between 1 and 10
+pub struct FsrsScheduler;
+
+impl FsrsScheduler {
+    /// Default FSRS v4.5 model weights
+    const WEIGHTS: [f32; 17] = [
+        0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461,
+        2.1072, 0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+    ];
+    const DESIRED_RETENTION: f32 = 0.9;
+    const DECAY: f32 = -0.5;
+    const FACTOR: f32 = 19.0 / 81.0;
+    const MIN_INTERVAL_DAYS: f32 = 1.0;
+    const MAX_INTERVAL_DAYS: f32 = 36500.0;
+    const AGAIN: f32 = 1.0; // FSRS grade for an incorrect answer
+    const GOOD: f32 = 3.0; // FSRS grade for a correct answer
+
+    /// Probability of recalling a kanji after the given number of days
+    pub fn retrievability(elapsed_days: f32, stability: f32) -> f32 {
+        (1.0 + Self::FACTOR * elapsed_days / stability).powf(Self::DECAY)
+    }
+
+    fn elapsed_days(stat: &StatValue, now: SystemTime) -> f32 {
+        let last_review = stat.due_time - stat.review_interval;
+        now.duration_since(last_review)
+            .unwrap_or_default()
+            .as_secs_f32()
+            / days_to_seconds(1) as f32
+    }
+
+    fn initial_difficulty(grade: f32) -> f32 {
+        (Self::WEIGHTS[4] - (grade - 3.0) * Self::WEIGHTS[5]).clamp(1.0, 10.0)
+    }
+
+    fn next_difficulty(difficulty: f32, grade: f32) -> f32 {
+        let next = difficulty - Self::WEIGHTS[6] * (grade - 3.0);
+        (Self::WEIGHTS[7] * Self::initial_difficulty(Self::GOOD) + (1.0 - Self::WEIGHTS[7]) * next)
+            .clamp(1.0, 10.0)
+    }
+
+    fn next_recall_stability(difficulty: f32, stability: f32, retrievability: f32) -> f32 {
+        let w = &Self::WEIGHTS;
+        stability
+            * (w[8].exp()
+                * (11.0 - difficulty)
+                * stability.powf(-w[9])
+                * ((w[10] * (1.0 - retrievability)).exp() - 1.0)
+                + 1.0)
+    }
+
+    fn next_forget_stability(difficulty: f32, stability: f32, retrievability: f32) -> f32 {
+        let w = &Self::WEIGHTS;
+        w[11]
+            * difficulty.powf(-w[12])
+            * ((stability + 1.0).powf(w[13]) - 1.0)
+            * (w[14] * (1.0 - retrievability)).exp()
+    }
+
+    fn interval_days(stability: f32) -> f32 {
+        let interval =
+            stability / Self::FACTOR * (Self::DESIRED_RETENTION.powf(1.0 / Self::DECAY) - 1.0);
+        interval
+            .round()
+            .clamp(Self::MIN_INTERVAL_DAYS, Self::MAX_INTERVAL_DAYS)
+    }
+}
+
+impl Scheduler for FsrsScheduler {
+    fn review(&self, stat: &mut StatValue, is_correct: bool, now: SystemTime) {
+        let grade = if is_correct { Self::GOOD } else { Self::AGAIN };
+
+        if stat.stability <= 0.0 {
+            // First review of the kanji
+            stat.stability = Self::WEIGHTS[grade as usize - 1];
+            stat.difficulty = Self::initial_difficulty(grade);
+        } else {
+            let retrievability =
+                Self::retrievability(Self::elapsed_days(stat, now), stat.stability);
+            stat.difficulty = Self::next_difficulty(stat.difficulty, grade);
+            stat.stability = if is_correct {
+                Self::next_recall_stability(stat.difficulty, stat.stability, retrievability)
+            } else {
+                Self::next_forget_stability(stat.difficulty, stat.stability, retrievability)
+            };
+        }
+
+        if is_correct {
+            stat.repetitions += 1;
+            stat.review_interval = days(Self::interval_days(stat.stability));
+        } else {
+            stat.repetitions = 0;
+            stat.lapses += 1;
+            stat.review_interval = Default::default();
+        }
+    }
+
+    /// Kanji with the lowest probability of being recalled are reviewed first
+    fn sort_due_kanji(&self, due_kanji: &mut [(char, &StatValue)], now: SystemTime) {
+        due_kanji.sort_unstable_by(|(_, a), (_, b)| {
+            let a = Self::retrievability(Self::elapsed_days(a, now), a.stability.max(f32::EPSILON));
+            let b = Self::retrievability(Self::elapsed_days(b, now), b.stability.max(f32::EPSILON));
+            a.total_cmp(&b)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::tests::{graduated, time, THRESHOLD};
+
+    #[test]
+    fn first_review_uses_the_initial_stability_and_difficulty() {
+        let stat = graduated(&FsrsScheduler, 0);
+        assert_eq!(stat.stability, FsrsScheduler::WEIGHTS[2]);
+        assert_eq!(stat.difficulty, FsrsScheduler::WEIGHTS[4]);
+        // The interval for 90 % retention equals the stability
+        assert_eq!(stat.review_interval, days(4.0));
+    }
+
+    #[test]
+    fn correct_reviews_increase_the_stability() {
+        let mut stat = graduated(&FsrsScheduler, 0);
+        let (stability, interval) = (stat.stability, stat.review_interval);
+        let due_time = stat.due_time;
+        FsrsScheduler.record_answer(&mut stat, true, THRESHOLD, due_time);
+        assert!(stat.stability > stability);
+        assert!(stat.review_interval > interval);
+        assert_eq!(stat.repetitions, 2);
+    }
+
+    #[test]
+    fn lapses_decrease_the_stability_and_increase_the_difficulty() {
+        let mut stat = graduated(&FsrsScheduler, 0);
+        let (stability, difficulty) = (stat.stability, stat.difficulty);
+        FsrsScheduler.record_answer(&mut stat, false, THRESHOLD, time(4));
+        assert!(stat.stability < stability);
+        assert!(stat.difficulty > difficulty);
+        assert_eq!(stat.review_interval, Default::default());
+        assert_eq!(stat.lapses, 1);
+    }
+
+    #[test]
+    fn retrievability_is_the_desired_retention_after_the_stability() {
+        assert_eq!(FsrsScheduler::retrievability(0.0, 5.0), 1.0);
+        let retrievability = FsrsScheduler::retrievability(5.0, 5.0);
+        assert!((retrievability - FsrsScheduler::DESIRED_RETENTION).abs() < 1e-4);
+    }
+}
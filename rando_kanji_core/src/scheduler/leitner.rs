@@ -0,0 +1,112 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use rand::seq::SliceRandom;
+
+use super::{days, Scheduler};
+use crate::config::StatValue;
+
+/// Scheduler based on the Leitner box system. Every correct review moves a kanji
+/// to the next box with a longer review interval, a lapse moves it back to learning
+pub struct LeitnerScheduler;
+
+impl LeitnerScheduler {
+    const BOX_INTERVALS_DAYS: [f32; 5] = [1.0, 3.0, 7.0, 14.0, 30.0];
+    const MAX_BOX: u8 = Self::BOX_INTERVALS_DAYS.len() as u8;
+}
+
+impl Scheduler for LeitnerScheduler {
+    fn review(&self, stat: &mut StatValue, is_correct: bool, _now: SystemTime) {
+        if is_correct {
+            stat.leitner_box = stat.leitner_box.saturating_add(1).min(Self::MAX_BOX);
+            stat.repetitions += 1;
+            stat.review_interval = days(Self::BOX_INTERVALS_DAYS[stat.leitner_box as usize - 1]);
+        } else {
+            stat.leitner_box = 0;
+            stat.repetitions = 0;
+            stat.lapses += 1;
+            stat.review_interval = Default::default();
+        }
+    }
+
+    /// Kanji in lower boxes are shown more often
+    fn next_kanji(
+        &self,
+        kanji_pool: &[char],
+        statistics: &HashMap<char, StatValue>,
+        last_kanji: char,
+    ) -> Option<char> {
+        if kanji_pool.len() == 1 {
+            return kanji_pool.first().copied();
+        }
+        let candidates = kanji_pool
+            .iter()
+            .filter(|kanji| **kanji != last_kanji)
+            .copied()
+            .collect::<Vec<char>>();
+        candidates
+            .choose_weighted(&mut rand::thread_rng(), |kanji| {
+                let leitner_box = statistics.get(kanji).map_or(0, |stat| stat.leitner_box);
+                (Self::MAX_BOX + 1 - leitner_box) as u32
+            })
+            .ok()
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::tests::{graduated, time, THRESHOLD};
+
+    #[test]
+    fn correct_reviews_move_kanji_up_to_the_last_box() {
+        let mut stat = graduated(&LeitnerScheduler, 0);
+        assert_eq!(stat.leitner_box, 1);
+        for interval in [3.0, 7.0, 14.0, 30.0, 30.0] {
+            let due_time = stat.due_time;
+            LeitnerScheduler.record_answer(&mut stat, true, THRESHOLD, due_time);
+            assert_eq!(stat.review_interval, days(interval));
+        }
+        assert_eq!(stat.leitner_box, LeitnerScheduler::MAX_BOX);
+    }
+
+    #[test]
+    fn invalid_boxes_are_clamped_to_the_last_box() {
+        let mut stat = graduated(&LeitnerScheduler, 0);
+        stat.leitner_box = u8::MAX; // From an edited profile
+        let due_time = stat.due_time;
+        LeitnerScheduler.record_answer(&mut stat, true, THRESHOLD, due_time);
+        assert_eq!(stat.leitner_box, LeitnerScheduler::MAX_BOX);
+        assert_eq!(stat.review_interval, days(30.0));
+    }
+
+    #[test]
+    fn lapses_move_kanji_back_to_learning() {
+        let mut stat = graduated(&LeitnerScheduler, 0);
+        LeitnerScheduler.record_answer(&mut stat, true, THRESHOLD, time(1));
+        LeitnerScheduler.record_answer(&mut stat, false, THRESHOLD, time(2));
+        assert_eq!(stat.leitner_box, 0);
+        assert_eq!(stat.lapses, 1);
+        assert!(stat.is_learning());
+
+        LeitnerScheduler.record_answer(&mut stat, true, THRESHOLD, time(2));
+        assert_eq!(stat.leitner_box, 1);
+    }
+
+    #[test]
+    fn next_kanji_is_not_the_last_kanji() {
+        let pool = ['一', '二'];
+        let statistics = HashMap::new();
+        for _ in 0..20 {
+            assert_eq!(
+                LeitnerScheduler.next_kanji(&pool, &statistics, '一'),
+                Some('二')
+            );
+        }
+        assert_eq!(
+            LeitnerScheduler.next_kanji(&['一'], &statistics, '一'),
+            Some('一')
+        );
+        assert_eq!(LeitnerScheduler.next_kanji(&[], &statistics, '一'), None);
+    }
+}
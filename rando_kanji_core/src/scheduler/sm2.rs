@@ -0,0 +1,82 @@
+use std::time::{Duration, SystemTime};
+
+use super::{days_to_seconds, Scheduler};
+use crate::config::StatValue;
+
+/// Scheduler based on the SuperMemo SM-2 algorithm
+pub struct Sm2Scheduler;
+
+impl Sm2Scheduler {
+    pub const DEFAULT_EASE_FACTOR: f32 = 2.5;
+    pub const MIN_EASE_FACTOR: f32 = 1.3;
+    const FIRST_INTERVAL: Duration = Duration::from_secs(days_to_seconds(1));
+    const SECOND_INTERVAL: Duration = Duration::from_secs(days_to_seconds(6));
+    const CORRECT_QUALITY: u8 = 4; // SM-2 response quality for a correct answer
+    const INCORRECT_QUALITY: u8 = 1; // SM-2 response quality for an incorrect answer
+}
+
+impl Scheduler for Sm2Scheduler {
+    fn review(&self, stat: &mut StatValue, is_correct: bool, _now: SystemTime) {
+        let quality = if is_correct {
+            Self::CORRECT_QUALITY
+        } else {
+            Self::INCORRECT_QUALITY
+        };
+        let q = quality as f32;
+        stat.ease_factor = (stat.ease_factor + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02))
+            .max(Self::MIN_EASE_FACTOR);
+
+        if quality < 3 {
+            stat.repetitions = 0;
+            stat.lapses += 1;
+            stat.review_interval = Duration::ZERO;
+        } else {
+            stat.repetitions += 1;
+            stat.review_interval = match stat.repetitions {
+                1 => Self::FIRST_INTERVAL,
+                2 => Self::SECOND_INTERVAL,
+                _ => stat.review_interval.mul_f32(stat.ease_factor),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::tests::{graduated, time, THRESHOLD};
+
+    #[test]
+    fn intervals_grow_with_the_ease_factor() {
+        let mut stat = graduated(&Sm2Scheduler, 0);
+        assert_eq!(stat.review_interval, Sm2Scheduler::FIRST_INTERVAL);
+
+        Sm2Scheduler.record_answer(&mut stat, true, THRESHOLD, time(1));
+        assert_eq!(stat.review_interval, Sm2Scheduler::SECOND_INTERVAL);
+        assert_eq!(stat.due_time, time(7));
+
+        Sm2Scheduler.record_answer(&mut stat, true, THRESHOLD, time(7));
+        assert_eq!(stat.repetitions, 3);
+        assert_eq!(
+            stat.review_interval,
+            Duration::from_secs(days_to_seconds(15))
+        );
+        assert_eq!(stat.ease_factor, Sm2Scheduler::DEFAULT_EASE_FACTOR);
+    }
+
+    #[test]
+    fn lapses_lower_the_ease_factor_down_to_the_minimum() {
+        let mut stat = graduated(&Sm2Scheduler, 0);
+        Sm2Scheduler.record_answer(&mut stat, false, THRESHOLD, time(1));
+        assert!((stat.ease_factor - 1.96).abs() < 1e-5);
+        assert_eq!(stat.review_interval, Duration::ZERO);
+        assert_eq!(stat.repetitions, 0);
+
+        for day in 2..6 {
+            Sm2Scheduler.record_answer(&mut stat, true, THRESHOLD, time(day));
+            Sm2Scheduler.record_answer(&mut stat, false, THRESHOLD, time(day));
+        }
+        assert_eq!(stat.ease_factor, Sm2Scheduler::MIN_EASE_FACTOR);
+        assert_eq!(stat.lapses, 5);
+    }
+}
@@ -0,0 +1,294 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use crate::{
+    config::{Config, ProfileId, QuizMode},
+    kanji::KanjiDealer,
+    review_log::{ReviewLog, ReviewLogEntry},
+    romaji,
+    statistics::KanjiHistory,
+};
+
+/// The kanji currently asked in a quiz session
+pub struct Question {
+    pub kanji: char,
+    pub prompt: String, // The kanji, or its meaning or reading in the reverse quiz mode
+    pub options: Vec<String>, // Answer options, empty in the typed reading quiz mode
+    pub correct_index: usize,
+    pub correct_option: String,
+    pub shown_time: SystemTime,
+}
+
+/// Asks kanji of the loaded profile and records the answers,
+/// independent of how the questions are shown
+pub struct QuizSession {
+    pub config: Config,
+    pub kanji_dealer: KanjiDealer,
+    question: Option<Question>,
+    histories: Option<(ProfileId, HashMap<char, KanjiHistory>)>, // Read from the review log when first shown
+}
+
+impl QuizSession {
+    pub fn new(config: Config) -> Self {
+        let kanji_dealer = KanjiDealer::new(&config);
+        Self {
+            config,
+            kanji_dealer,
+            question: None,
+            histories: None,
+        }
+    }
+
+    /// Deal the next kanji according to the quiz mode of the profile.
+    /// None if there is no kanji to ask because every kanji has been suspended
+    pub fn next_question(&mut self) -> Option<&Question> {
+        self.kanji_dealer.update_kanji_pool(&mut self.config);
+        let text_option = self.config.button_text_option;
+        let is_reverse = self.config.quiz_mode.is_reverse();
+
+        let question = if self.config.quiz_mode == QuizMode::TypedReading {
+            let record = match self.kanji_dealer.deal_kanji_record(&self.config) {
+                Some(record) => record,
+                None => {
+                    self.question = None;
+                    return None;
+                }
+            };
+            Question {
+                kanji: record.kanji,
+                prompt: record.kanji.to_string(),
+                options: Vec::new(),
+                correct_index: 0,
+                correct_option: record.joyo_reading.trim().to_string(),
+                shown_time: SystemTime::now(),
+            }
+        } else {
+            let (correct_index, candidates) =
+                match self.kanji_dealer.deal_kanji_candidates(&self.config) {
+                    Some(candidates) => candidates,
+                    None => {
+                        self.question = None;
+                        return None;
+                    }
+                };
+            let correct_index = correct_index as usize;
+            let correct_record = candidates[correct_index];
+            // The reverse quiz mode asks for the kanji of a meaning or reading
+            let options = candidates
+                .iter()
+                .map(|record| {
+                    if is_reverse {
+                        record.kanji.to_string()
+                    } else {
+                        record.as_option_text(text_option)
+                    }
+                })
+                .collect::<Vec<String>>();
+            Question {
+                kanji: correct_record.kanji,
+                prompt: if is_reverse {
+                    correct_record.as_option_text(text_option)
+                } else {
+                    correct_record.kanji.to_string()
+                },
+                correct_option: options[correct_index].clone(),
+                options,
+                correct_index,
+                shown_time: SystemTime::now(),
+            }
+        };
+        Some(self.question.insert(question))
+    }
+
+    #[inline]
+    pub fn question(&self) -> Option<&Question> {
+        self.question.as_ref()
+    }
+
+    /// Check and record the chosen answer option of the current question
+    pub fn answer_option(&mut self, index: usize) -> bool {
+        let (is_correct, chosen_option) = match &self.question {
+            Some(question) => (
+                index == question.correct_index,
+                question.options.get(index).cloned().unwrap_or_default(),
+            ),
+            None => return false,
+        };
+        self.record_answer(&chosen_option, is_correct);
+        is_correct
+    }
+
+    /// Check and record a reading typed in rōmaji or kana
+    pub fn answer_typed(&mut self, text: &str) -> bool {
+        let kanji = match &self.question {
+            Some(question) => question.kanji,
+            None => return false,
+        };
+        let answer = romaji::to_hiragana(text.trim());
+        let is_correct = self
+            .kanji_dealer
+            .find_record_by_char(&kanji)
+            .is_correct_reading(&answer);
+        self.record_answer(&answer, is_correct);
+        is_correct
+    }
+
+    /// Answers given to a kanji by the learner of the profile
+    pub fn history(&mut self, kanji: char) -> KanjiHistory {
+        let profile = self.config.profile.id;
+        let histories = match &mut self.histories {
+            Some((loaded_profile, histories)) if *loaded_profile == profile => histories,
+            histories => {
+                let entries = match ReviewLog::read_or_empty(&self.config.review_log_path()) {
+                    Ok(entries) => entries,
+                    Err(err) => {
+                        eprintln!("Could not read review log: {}", err);
+                        Vec::new()
+                    }
+                };
+                &mut histories
+                    .insert((profile, KanjiHistory::per_kanji(&entries)))
+                    .1
+            }
+        };
+        histories.get(&kanji).cloned().unwrap_or_default()
+    }
+
+    /// Read the answer history again after the review log has been changed outside of the session
+    #[inline]
+    pub fn reload_history(&mut self) {
+        self.histories = None;
+    }
+
+    fn record_answer(&mut self, chosen_option: &str, is_correct: bool) {
+        let question = match &self.question {
+            Some(question) => question,
+            None => return,
+        };
+        let now = SystemTime::now();
+        let entry = ReviewLogEntry {
+            timestamp: now
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            kanji: question.kanji,
+            quiz_mode: self.config.quiz_mode,
+            text_option: self.config.button_text_option,
+            chosen_option: chosen_option.to_string(),
+            correct_option: question.correct_option.clone(),
+            is_correct,
+            response_time_ms: now
+                .duration_since(question.shown_time)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        };
+        if let Err(err) = ReviewLog::append(&self.config.review_log_path(), &entry) {
+            eprintln!("Error writing review log: {}", err);
+        }
+        if let Some((profile, histories)) = &mut self.histories {
+            if *profile == self.config.profile.id {
+                histories.entry(entry.kanji).or_default().add(&entry);
+            }
+        }
+
+        let scheduler = self.config.scheduler.get();
+        let learning_index_threshold = self.config.learning_index_threshold;
+        let stat = self.config.statistics_mut().entry(entry.kanji).or_default();
+        scheduler.record_answer(stat, is_correct, learning_index_threshold, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Profile, storage::Storage};
+
+    /// Each test has its own profile so that their review logs are kept apart
+    fn session(id: u32, quiz_mode: QuizMode) -> QuizSession {
+        Storage::use_test_data_dir();
+        let config = Config {
+            profile: Profile {
+                name: format!("Session test {}", id),
+                id: ProfileId(id),
+            },
+            quiz_mode,
+            ..Config::default()
+        };
+        QuizSession::new(config)
+    }
+
+    #[test]
+    fn answers_are_recorded_in_the_statistics_and_the_history() {
+        let mut session = session(101, QuizMode::MultipleChoice);
+        let question = session.next_question().unwrap();
+        let kanji = question.kanji;
+        assert_eq!(question.prompt, kanji.to_string());
+        assert_eq!(
+            question.options[question.correct_index],
+            question.correct_option
+        );
+        let wrong_index = (question.correct_index + 1) % question.options.len();
+        let wrong_option = question.options[wrong_index].clone();
+        let correct_index = question.correct_index;
+
+        assert_eq!(session.history(kanji).reviews, 0);
+        assert!(!session.answer_option(wrong_index));
+        assert!(session.answer_option(correct_index));
+        let history = session.history(kanji);
+        assert_eq!(history.reviews, 2);
+        assert_eq!(history.correct, 1);
+        assert_eq!(history.wrong_answers, [wrong_option]);
+        assert!(session.config.answer_statistics.contains_key(&kanji));
+
+        // The cached history is the one of the review log
+        let entries = ReviewLog::read(&session.config.review_log_path()).unwrap();
+        assert_eq!(entries.len(), 2);
+        session.reload_history();
+        assert_eq!(session.history(kanji).wrong_answers, history.wrong_answers);
+    }
+
+    #[test]
+    fn reverse_questions_ask_for_the_kanji() {
+        let mut session = session(102, QuizMode::Reverse);
+        let question = session.next_question().unwrap();
+        assert_eq!(question.correct_option, question.kanji.to_string());
+        assert_ne!(question.prompt, question.correct_option);
+        assert!(question
+            .options
+            .iter()
+            .all(|option| option.chars().count() == 1));
+        let kanji = question.kanji;
+        let correct_index = question.correct_index;
+        assert!(session.answer_option(correct_index));
+        assert!(session.config.reverse_statistics.contains_key(&kanji));
+        assert!(session.config.answer_statistics.is_empty());
+    }
+
+    #[test]
+    fn typed_answers_are_checked_against_the_readings() {
+        let mut session = session(103, QuizMode::TypedReading);
+        let kanji = session.next_question().unwrap().kanji;
+        let reading = session
+            .kanji_dealer
+            .find_record_by_char(&kanji)
+            .readings()
+            .accepted_readings()
+            .remove(0);
+        assert!(!session.answer_typed("xyz"));
+        assert!(session.answer_typed(&format!(" {} ", reading)));
+        assert_eq!(session.history(kanji).wrong_answers, ["xyz"]);
+    }
+
+    #[test]
+    fn no_question_when_every_kanji_is_suspended() {
+        let mut session = session(104, QuizMode::MultipleChoice);
+        session.config.deck_filter.excluded_kanji = session
+            .kanji_dealer
+            .kanjis
+            .iter()
+            .map(|record| record.kanji)
+            .collect();
+        assert!(session.next_question().is_none());
+        assert!(session.question().is_none());
+        assert!(!session.answer_option(0));
+    }
+}
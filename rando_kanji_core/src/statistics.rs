@@ -0,0 +1,298 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Datelike, Local};
+
+use crate::{
+    config::{Config, StatValue},
+    kanji::KanjiRecord,
+    review_log::ReviewLogEntry,
+};
+
+/// Learning progress of a profile prepared for plotting.
+/// Days are relative to the current day, i.e. 0 is today and -1 is yesterday
+#[derive(Default)]
+pub struct ProgressStatistics {
+    pub daily_reviews: Vec<[f64; 2]>,    // Number of answers per day
+    pub rolling_accuracy: Vec<[f64; 2]>, // Percentage of correct answers over the last days
+    pub learned_kanji: Vec<[f64; 2]>,    // Number of learned kanji at the end of each day
+    pub learned_reverse_kanji: Vec<[f64; 2]>, // Same for the reverse quiz mode
+    pub jlpt_levels: Vec<JlptProgress>,
+    pub total_reviews: usize,
+}
+
+pub struct JlptProgress {
+    pub level: u8, // 0 for kanji outside of the JLPT levels
+    pub learned: usize,
+    pub learning: usize,
+}
+
+impl JlptProgress {
+    pub fn label(&self) -> String {
+        if self.level == 0 {
+            "No JLPT".to_string()
+        } else {
+            format!("N{}", self.level)
+        }
+    }
+}
+
+/// Answers of the learner for a single kanji in all quiz modes
+#[derive(Default, Clone)]
+pub struct KanjiHistory {
+    pub reviews: usize,
+    pub correct: usize,
+    pub first_review: Option<SystemTime>,
+    pub wrong_answers: Vec<String>, // Latest distinct wrong answers, newest first
+}
+
+impl KanjiHistory {
+    const MAX_WRONG_ANSWERS: usize = 3;
+
+    /// The history of every kanji in the review log
+    pub fn per_kanji(entries: &[ReviewLogEntry]) -> HashMap<char, Self> {
+        let mut histories = HashMap::<char, Self>::new();
+        for entry in entries {
+            histories.entry(entry.kanji).or_default().add(entry);
+        }
+        histories
+    }
+
+    /// Add an answer that is newer than the answers already in the history
+    pub fn add(&mut self, entry: &ReviewLogEntry) {
+        self.reviews += 1;
+        if entry.is_correct {
+            self.correct += 1;
+        } else {
+            self.wrong_answers
+                .retain(|answer| *answer != entry.chosen_option);
+            self.wrong_answers.insert(0, entry.chosen_option.clone());
+            self.wrong_answers.truncate(Self::MAX_WRONG_ANSWERS);
+        }
+        self.first_review = Some(match self.first_review {
+            Some(time) => time.min(entry.time()),
+            None => entry.time(),
+        });
+    }
+
+    /// Percentage of correct answers
+    pub fn accuracy(&self) -> f64 {
+        if self.reviews == 0 {
+            0.0
+        } else {
+            self.correct as f64 / self.reviews as f64 * 100.0
+        }
+    }
+}
+
+impl ProgressStatistics {
+    const ROLLING_ACCURACY_DAYS: i64 = 7;
+
+    pub fn new(entries: &[ReviewLogEntry], config: &Config, kanjis: &[KanjiRecord]) -> Self {
+        let today = Self::day_number(SystemTime::now());
+        let relative_day = |day: i64| (day - today) as f64;
+
+        // Answers per day as (total, correct)
+        let mut days = BTreeMap::<i64, (u32, u32)>::new();
+        for entry in entries {
+            let day = days.entry(Self::day_number(entry.time())).or_default();
+            day.0 += 1;
+            if entry.is_correct {
+                day.1 += 1;
+            }
+        }
+
+        let daily_reviews = days
+            .iter()
+            .map(|(day, (total, _))| [relative_day(*day), *total as f64])
+            .collect();
+
+        let rolling_accuracy = days
+            .keys()
+            .map(|day| {
+                let (total, correct) = days
+                    .range(day - Self::ROLLING_ACCURACY_DAYS + 1..=*day)
+                    .fold((0, 0), |acc, (_, (total, correct))| {
+                        (acc.0 + total, acc.1 + correct)
+                    });
+                [relative_day(*day), correct as f64 / total as f64 * 100.0]
+            })
+            .collect();
+
+        Self {
+            daily_reviews,
+            rolling_accuracy,
+            learned_kanji: Self::learned_kanji_per_day(
+                entries.iter().filter(|entry| !entry.quiz_mode.is_reverse()),
+                config,
+                today,
+            ),
+            learned_reverse_kanji: Self::learned_kanji_per_day(
+                entries.iter().filter(|entry| entry.quiz_mode.is_reverse()),
+                config,
+                today,
+            ),
+            jlpt_levels: Self::jlpt_levels(config, kanjis),
+            total_reviews: entries.len(),
+        }
+    }
+
+    /// Replay the review log to find out how many kanji were learned by the end of each day
+    fn learned_kanji_per_day<'a>(
+        entries: impl Iterator<Item = &'a ReviewLogEntry>,
+        config: &Config,
+        today: i64,
+    ) -> Vec<[f64; 2]> {
+        let scheduler = config.scheduler.get();
+        let mut statistics = HashMap::<char, StatValue>::new();
+        let mut learned_kanji = Vec::new();
+        let mut entries = entries.peekable();
+
+        while let Some(entry) = entries.next() {
+            let stat = statistics.entry(entry.kanji).or_default();
+            scheduler.record_answer(
+                stat,
+                entry.is_correct,
+                config.learning_index_threshold,
+                entry.time(),
+            );
+            let day = Self::day_number(entry.time());
+            let is_last_of_day =
+                entries.peek().map(|next| Self::day_number(next.time())) != Some(day);
+            if is_last_of_day {
+                let learned = statistics
+                    .values()
+                    .filter(|stat| stat.learning_index >= config.learning_index_threshold)
+                    .count();
+                learned_kanji.push([(day - today) as f64, learned as f64]);
+            }
+        }
+        learned_kanji
+    }
+
+    fn jlpt_levels(config: &Config, kanjis: &[KanjiRecord]) -> Vec<JlptProgress> {
+        let jlpt_by_kanji = kanjis
+            .iter()
+            .map(|record| (record.kanji, record.jlpt))
+            .collect::<HashMap<char, u8>>();
+
+        let mut levels = BTreeMap::<u8, JlptProgress>::new();
+        for (kanji, stat) in config.answer_statistics.iter() {
+            let level = jlpt_by_kanji.get(kanji).copied().unwrap_or_default();
+            let progress = levels.entry(level).or_insert(JlptProgress {
+                level,
+                learned: 0,
+                learning: 0,
+            });
+            if stat.learning_index >= config.learning_index_threshold {
+                progress.learned += 1;
+            } else {
+                progress.learning += 1;
+            }
+        }
+        levels.into_values().collect()
+    }
+
+    /// Days in the local time zone, so that late evening answers count for the same day
+    fn day_number(time: SystemTime) -> i64 {
+        DateTime::<Local>::from(time).num_days_from_ce() as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Days;
+
+    use super::*;
+    use crate::config::{ButtonTextOption, QuizMode};
+
+    fn entry(timestamp: u64, kanji: char, chosen_option: &str, is_correct: bool) -> ReviewLogEntry {
+        ReviewLogEntry {
+            timestamp,
+            kanji,
+            quiz_mode: QuizMode::MultipleChoice,
+            text_option: ButtonTextOption::Kana,
+            chosen_option: chosen_option.to_string(),
+            correct_option: "ア".to_string(),
+            is_correct,
+            response_time_ms: 1000,
+        }
+    }
+
+    /// Timestamp of a local time on a day before today
+    fn local_timestamp(days_ago: u64, hour: u32, minute: u32) -> u64 {
+        Local::now()
+            .date_naive()
+            .checked_sub_days(Days::new(days_ago))
+            .and_then(|date| date.and_hms_opt(hour, minute, 0))
+            .and_then(|time| time.and_local_timezone(Local).earliest())
+            .unwrap()
+            .timestamp() as u64
+    }
+
+    #[test]
+    fn progress_statistics_group_answers_by_local_day() {
+        let mut reverse = entry(local_timestamp(1, 12, 0), '哀', "亜", true);
+        reverse.quiz_mode = QuizMode::Reverse;
+        let entries = [
+            entry(local_timestamp(2, 23, 30), '亜', "ア", true),
+            entry(local_timestamp(1, 0, 30), '亜', "イ", false),
+            reverse,
+            entry(local_timestamp(1, 23, 30), '亜', "ア", true),
+            entry(local_timestamp(0, 0, 30), '亜', "ア", true),
+        ];
+        let statistics = ProgressStatistics::new(&entries, &Config::default(), &[]);
+        assert_eq!(
+            statistics.daily_reviews,
+            [[-2.0, 1.0], [-1.0, 3.0], [0.0, 1.0]]
+        );
+        let accuracy = statistics
+            .rolling_accuracy
+            .iter()
+            .map(|[day, accuracy]| [*day, accuracy.round()])
+            .collect::<Vec<_>>();
+        assert_eq!(accuracy, [[-2.0, 100.0], [-1.0, 75.0], [0.0, 80.0]]);
+        let learned_days =
+            |learned: &[[f64; 2]]| learned.iter().map(|[day, _]| *day).collect::<Vec<_>>();
+        assert_eq!(learned_days(&statistics.learned_kanji), [-2.0, -1.0, 0.0]);
+        assert_eq!(learned_days(&statistics.learned_reverse_kanji), [-1.0]);
+        assert_eq!(statistics.total_reviews, 5);
+    }
+
+    #[test]
+    fn kanji_history_keeps_the_latest_distinct_wrong_answers() {
+        let entries = [
+            entry(10, '亜', "イ", false),
+            entry(20, '亜', "ウ", false),
+            entry(30, '哀', "エ", false),
+            entry(40, '亜', "ア", true),
+            entry(50, '亜', "エ", false),
+            entry(60, '亜', "オ", false),
+            entry(70, '亜', "ウ", false),
+        ];
+        let histories = KanjiHistory::per_kanji(&entries);
+        let history = &histories[&'亜'];
+        assert_eq!(history.reviews, 6);
+        assert_eq!(history.correct, 1);
+        assert_eq!(history.wrong_answers, ["ウ", "オ", "エ"]);
+        assert_eq!(history.first_review, Some(entries[0].time()));
+        assert!((history.accuracy() - 100.0 / 6.0).abs() < 1e-9);
+        assert_eq!(histories[&'哀'].reviews, 1);
+    }
+
+    #[test]
+    fn kanji_history_adds_new_answers() {
+        let entries = [entry(10, '亜', "イ", false), entry(20, '亜', "ア", true)];
+        let mut history = KanjiHistory::per_kanji(&entries[..1])
+            .remove(&'亜')
+            .unwrap();
+        history.add(&entries[1]);
+        let expected = &KanjiHistory::per_kanji(&entries)[&'亜'];
+        assert_eq!(history.reviews, expected.reviews);
+        assert_eq!(history.correct, expected.correct);
+        assert_eq!(history.wrong_answers, expected.wrong_answers);
+        assert_eq!(history.first_review, expected.first_review);
+    }
+}
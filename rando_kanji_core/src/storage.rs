@@ -0,0 +1,552 @@
+use std::{
+    env,
+    error::Error,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, Profile, ProfileId};
+
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Location of the profile files, by default the platform data directory,
+/// e.g. `~/.local/share/rando_kanji` on Linux
+pub struct Storage;
+
+impl Storage {
+    pub const DATA_DIR_ENV_VAR: &'static str = "RANDO_KANJI_DATA_DIR";
+    pub const DATA_DIR_FLAG: &'static str = "--data-dir";
+    pub const RESOURCE_DIR_ENV_VAR: &'static str = "RANDO_KANJI_RESOURCE_DIR";
+    pub const BACKUP_COUNT: u32 = 5; // Previous saves kept of each profile
+    /// Saves within this time of the newest backup aren't backed up,
+    /// so that autosaves and saves after every answer don't push out the older backups
+    pub const BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+    const APP_DIR_NAME: &'static str = "rando_kanji";
+    const RESOURCE_DIR_NAME: &'static str = "res";
+    const TEMP_SUFFIX: &'static str = ".tmp";
+    const BACKUP_SUFFIX: &'static str = ".bak";
+
+    /// Use the data directory given with the command line flag, which takes precedence over
+    /// the environment variable. Returns the remaining arguments
+    pub fn take_data_dir_arg(
+        args: impl Iterator<Item = String>,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let (path, remaining) = Self::split_data_dir_arg(args)?;
+        if let Some(path) = path {
+            DATA_DIR_OVERRIDE
+                .set(path)
+                .map_err(|_| "The data directory can only be set once")?;
+        }
+        Ok(remaining)
+    }
+
+    /// Separate the data directory flag from the other arguments
+    fn split_data_dir_arg(
+        args: impl Iterator<Item = String>,
+    ) -> Result<(Option<PathBuf>, Vec<String>), Box<dyn Error>> {
+        let mut data_dir = None;
+        let mut remaining = Vec::new();
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            let path = if arg == Self::DATA_DIR_FLAG {
+                args.next()
+            } else if let Some(path) = arg
+                .strip_prefix(Self::DATA_DIR_FLAG)
+                .and_then(|rest| rest.strip_prefix('='))
+            {
+                Some(path.to_string())
+            } else {
+                remaining.push(arg);
+                continue;
+            };
+            // Another flag after --data-dir means the path was left out
+            let path = match path {
+                Some(path) if !path.is_empty() && !path.starts_with("--") => path,
+                _ => return Err(format!("Missing path after {}", Self::DATA_DIR_FLAG).into()),
+            };
+            if data_dir.replace(PathBuf::from(path)).is_some() {
+                return Err(format!("{} can only be given once", Self::DATA_DIR_FLAG).into());
+            }
+        }
+        Ok((data_dir, remaining))
+    }
+
+    pub fn data_dir() -> PathBuf {
+        if let Some(path) = DATA_DIR_OVERRIDE.get() {
+            return path.clone();
+        }
+        if let Some(path) = env::var_os(Self::DATA_DIR_ENV_VAR) {
+            return PathBuf::from(path);
+        }
+        match dirs::data_dir() {
+            Some(path) => path.join(Self::APP_DIR_NAME),
+            None => PathBuf::from("."), // No home directory, keep the files next to the app
+        }
+    }
+
+    /// Directory of the bundled kanji database. By default the resource directory next to the
+    /// executable or in one of its parent directories, which finds the project root when run by cargo
+    pub fn resource_dir() -> PathBuf {
+        if let Some(path) = env::var_os(Self::RESOURCE_DIR_ENV_VAR) {
+            return PathBuf::from(path);
+        }
+        if let Ok(exe_path) = env::current_exe() {
+            let resource_dir = exe_path
+                .ancestors()
+                .skip(1)
+                .map(|dir| dir.join(Self::RESOURCE_DIR_NAME))
+                .find(|dir| dir.is_dir());
+            if let Some(resource_dir) = resource_dir {
+                return resource_dir;
+            }
+        }
+        PathBuf::from(Self::RESOURCE_DIR_NAME)
+    }
+
+    /// Path of a file in the data directory
+    #[inline]
+    pub fn path(file_name: &str) -> PathBuf {
+        Self::data_dir().join(file_name)
+    }
+
+    /// Create the directory of a file about to be written if it doesn't exist yet
+    pub fn create_parent_dir(path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Box<dyn Error>> {
+        Self::write_atomic(path, |writer| {
+            Ok(serde_json::to_writer_pretty(writer, value)?)
+        })
+    }
+
+    /// Write to a temporary file which then replaces the file,
+    /// so that a crash while saving never leaves a truncated file behind
+    pub fn write_atomic(
+        path: &Path,
+        write: impl FnOnce(&mut BufWriter<File>) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        Self::create_parent_dir(path)?;
+        let temp_path = Self::with_suffix(path, Self::TEMP_SUFFIX);
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        write(&mut writer)?;
+        writer.into_inner()?.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// Copy the file to `<file>.bak1` and shift the older backups up to `.bak5`,
+    /// unless the newest backup is more recent than the backup interval.
+    /// Returns whether a backup was made
+    pub fn rotate_backups(path: &Path, now: SystemTime) -> Result<bool, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(false);
+        }
+        let newest_backup = Self::backup_path(path, 1);
+        if let Ok(modified) = newest_backup
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+        {
+            match now.duration_since(modified) {
+                Ok(age) if age < Self::BACKUP_INTERVAL => return Ok(false),
+                Err(_) => return Ok(false), // Backed up in the future, the clock was set back
+                Ok(_) => {}
+            }
+        }
+        for number in (1..Self::BACKUP_COUNT).rev() {
+            let backup_path = Self::backup_path(path, number);
+            if backup_path.exists() {
+                fs::rename(&backup_path, Self::backup_path(path, number + 1))?;
+            }
+        }
+        fs::copy(path, &newest_backup)?;
+        // Copies may keep the modification time of the profile on some platforms
+        File::options()
+            .write(true)
+            .open(&newest_backup)?
+            .set_modified(now)?;
+        Ok(true)
+    }
+
+    pub fn remove_backups(path: &Path) {
+        for number in 1..=Self::BACKUP_COUNT {
+            let backup_path = Self::backup_path(path, number);
+            if backup_path.exists() {
+                if let Err(err) = fs::remove_file(&backup_path) {
+                    eprintln!("Error deleting backup {}: {}", backup_path.display(), err);
+                }
+            }
+        }
+    }
+
+    fn backup_path(path: &Path, number: u32) -> PathBuf {
+        Self::with_suffix(path, &format!("{}{}", Self::BACKUP_SUFFIX, number))
+    }
+
+    fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+        let mut path = path.as_os_str().to_owned();
+        path.push(suffix);
+        PathBuf::from(path)
+    }
+}
+
+#[cfg(test)]
+impl Storage {
+    /// Keep the files written by tests in a temporary data directory of the test process
+    pub(crate) fn use_test_data_dir() -> PathBuf {
+        DATA_DIR_OVERRIDE
+            .get_or_init(|| {
+                let dir = env::temp_dir().join(format!("rando_kanji_{}_data", std::process::id()));
+                let _ = fs::remove_dir_all(&dir);
+                dir
+            })
+            .clone()
+    }
+}
+
+/// List of the saved profiles and the one used last, kept next to the profile files
+#[derive(Serialize, Deserialize, Default)]
+pub struct ProfileIndex {
+    pub last_used: Option<ProfileId>,
+    pub profiles: Vec<Profile>,
+    #[serde(default)]
+    next_id: u32, // Only ever increases so that the ID of a deleted profile isn't given out again
+}
+
+/// Profile files used to be in the working directory and had their own last used flag
+#[derive(Deserialize)]
+struct LegacyProfile {
+    profile: LegacyProfileName,
+    #[serde(default)]
+    was_used_last: bool,
+}
+
+/// The ID of older profiles is taken from the file name, it wasn't numeric before
+#[derive(Deserialize)]
+struct LegacyProfileName {
+    name: String,
+}
+
+impl LegacyProfile {
+    fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+impl ProfileIndex {
+    const FILE_NAME: &'static str = "profiles.json";
+    const LEGACY_DIR: &'static str = ".";
+
+    /// Load the index, or build it from the profile files when it doesn't exist yet
+    pub fn load() -> Self {
+        let path = Storage::path(Self::FILE_NAME);
+        if path.exists() {
+            match Self::from_file(&path) {
+                Ok(index) => return index,
+                Err(err) => eprintln!("Error reading profile index, rebuilding it: {}", err),
+            }
+        }
+        let index = Self::rebuild();
+        if let Err(err) = index.save() {
+            eprintln!("Error saving profile index: {}", err);
+        }
+        index
+    }
+
+    #[inline]
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        Storage::write_json(&Storage::path(Self::FILE_NAME), self)
+    }
+
+    /// Add or rename a profile in the index
+    pub fn update(&mut self, profile: &Profile) {
+        match self
+            .profiles
+            .iter_mut()
+            .find(|entry| entry.id == profile.id)
+        {
+            Some(entry) => entry.name = profile.name.clone(),
+            None => {
+                self.profiles.push(profile.clone());
+                self.profiles.sort_unstable_by_key(|entry| entry.id);
+            }
+        }
+        self.next_id = self.next_id.max(profile.id.0 + 1);
+    }
+
+    /// Reserve the ID of a new profile, higher than any ID given out before
+    pub fn take_next_id(&mut self) -> ProfileId {
+        // Indexes of older versions have no counter, continue after the highest ID
+        let id = self
+            .profiles
+            .iter()
+            .map(|profile| profile.id.0 + 1)
+            .fold(self.next_id.max(ProfileId::default().0), u32::max);
+        self.next_id = id + 1;
+        ProfileId(id)
+    }
+
+    pub fn remove(&mut self, profile: ProfileId) {
+        self.profiles.retain(|entry| entry.id != profile);
+        if self.last_used == Some(profile) {
+            self.last_used = None;
+        }
+    }
+
+    fn from_file(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Find the profile files in the data directory after copying over
+    /// profiles from the working directory of older versions
+    fn rebuild() -> Self {
+        let data_dir = Storage::data_dir();
+        let legacy_dir = Path::new(Self::LEGACY_DIR);
+        let is_legacy_dir = match (data_dir.canonicalize(), legacy_dir.canonicalize()) {
+            (Ok(data_dir), Ok(legacy_dir)) => data_dir == legacy_dir,
+            _ => false,
+        };
+        if !is_legacy_dir {
+            Self::copy_legacy_profiles(legacy_dir, &data_dir);
+        }
+
+        let mut index = Self::default();
+        for (id, path) in Self::profile_files(&data_dir) {
+            match LegacyProfile::from_file(&path) {
+                Ok(legacy) => {
+                    if legacy.was_used_last || index.last_used.is_none() {
+                        index.last_used = Some(id);
+                    }
+                    index.update(&Profile {
+                        name: legacy.profile.name,
+                        id,
+                    });
+                }
+                Err(err) => eprintln!("Skipping profile {}: {}", path.display(), err),
+            }
+        }
+        index
+    }
+
+    fn copy_legacy_profiles(legacy_dir: &Path, data_dir: &Path) {
+        let profile_files = Self::profile_files(legacy_dir);
+        if profile_files.is_empty() {
+            return;
+        }
+        if let Err(err) = fs::create_dir_all(data_dir) {
+            eprintln!(
+                "Error creating data directory {}: {}",
+                data_dir.display(),
+                err
+            );
+            return;
+        }
+        for (id, path) in profile_files {
+            let review_log = Config::review_log_file_name(id);
+            let files = [
+                (path, data_dir.join(Config::file_name(id))),
+                (legacy_dir.join(&review_log), data_dir.join(&review_log)),
+            ];
+            for (from, to) in files {
+                if from.exists() && !to.exists() {
+                    match fs::copy(&from, &to) {
+                        Ok(_) => eprintln!("Copied {} to {}", from.display(), to.display()),
+                        Err(err) => eprintln!("Error copying {}: {}", from.display(), err),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Profile files in a directory by their ID
+    fn profile_files(directory: &Path) -> Vec<(ProfileId, PathBuf)> {
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                let id = file_name
+                    .strip_prefix(Config::FILE_PREFIX)?
+                    .strip_suffix(Config::FILE_EXTENSION)?
+                    .parse::<u32>()
+                    .ok()?;
+                Some((ProfileId(id), entry.path()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// An empty directory for the files of a test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rando_kanji_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn hours(hours: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(hours * 60 * 60)
+    }
+
+    #[test]
+    fn rotate_backups_keeps_one_backup_per_interval() {
+        let dir = test_dir("rotate_backups");
+        let path = dir.join("config1.json");
+        let save = |text: &str, hour: u64| {
+            let is_backed_up = Storage::rotate_backups(&path, hours(hour)).unwrap();
+            Storage::write_atomic(&path, |writer| Ok(writer.write_all(text.as_bytes())?)).unwrap();
+            is_backed_up
+        };
+        let backup = |number: u32| fs::read_to_string(Storage::backup_path(&path, number)).ok();
+
+        assert!(!save("a", 0)); // Nothing to back up yet
+        assert!(save("b", 0));
+        assert!(!save("c", 0)); // Saved again within the interval
+        assert_eq!(backup(1).as_deref(), Some("a"));
+        for (hour, text) in (2..).zip(["d", "e", "f", "g", "h"]) {
+            assert!(save(text, hour));
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "h");
+        let backups = (1..=Storage::BACKUP_COUNT + 1)
+            .map(backup)
+            .collect::<Vec<_>>();
+        let expected = ["g", "f", "e", "d", "c"].map(|text| Some(text.to_string()));
+        assert_eq!(backups[..5], expected);
+        assert_eq!(backups[5], None);
+
+        Storage::remove_backups(&path);
+        assert_eq!(backup(1), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_atomic_replaces_the_file_without_leaving_the_temporary_file() {
+        let dir = test_dir("write_atomic");
+        let path = dir.join("profile").join("config1.json");
+        Storage::write_json(&path, &vec![1, 2]).unwrap(); // Creates the missing directory
+        Storage::write_json(&path, &vec![3]).unwrap();
+        let value: Vec<u32> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value, [3]);
+        assert!(!Storage::with_suffix(&path, Storage::TEMP_SUFFIX).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_atomic_keeps_the_file_when_writing_fails() {
+        let dir = test_dir("write_atomic_error");
+        let path = dir.join("config1.json");
+        Storage::write_atomic(&path, |writer| Ok(writer.write_all(b"saved")?)).unwrap();
+        let result = Storage::write_atomic(&path, |writer| {
+            writer.write_all(b"trunc")?;
+            Err("disk full".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "saved");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn profile(id: u32, name: &str) -> Profile {
+        Profile {
+            name: name.to_string(),
+            id: ProfileId(id),
+        }
+    }
+
+    #[test]
+    fn profile_index_renames_and_removes_profiles() {
+        let dir = test_dir("profile_index");
+        let path = dir.join(ProfileIndex::FILE_NAME);
+        let mut index = ProfileIndex::default();
+        index.update(&profile(2, "Kana"));
+        index.update(&profile(1, "Default profile"));
+        index.update(&profile(2, "Kanji"));
+        index.last_used = Some(ProfileId(2));
+        Storage::write_json(&path, &index).unwrap();
+
+        let mut index = ProfileIndex::from_file(&path).unwrap();
+        let names = index
+            .profiles
+            .iter()
+            .map(|profile| (profile.id.0, profile.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(names, [(1, "Default profile"), (2, "Kanji")]);
+        assert_eq!(index.last_used, Some(ProfileId(2)));
+
+        index.remove(ProfileId(1));
+        assert_eq!(index.last_used, Some(ProfileId(2)));
+        index.remove(ProfileId(2));
+        assert!(index.profiles.is_empty());
+        assert_eq!(index.last_used, None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn take_next_id_never_reuses_the_id_of_a_deleted_profile() {
+        let mut index = ProfileIndex::default();
+        assert_eq!(index.take_next_id(), ProfileId(1));
+        index.update(&profile(1, "Default profile"));
+        let id = index.take_next_id();
+        assert_eq!(id, ProfileId(2));
+        index.update(&profile(id.0, "Second"));
+        index.remove(id);
+        assert_eq!(index.take_next_id(), ProfileId(3));
+    }
+
+    #[test]
+    fn take_next_id_continues_after_the_profiles_of_an_index_without_counter() {
+        let json =
+            r#"{"last_used": 4, "profiles": [{"name": "A", "id": 1}, {"name": "B", "id": 4}]}"#;
+        let mut index: ProfileIndex = serde_json::from_str(json).unwrap();
+        assert_eq!(index.take_next_id(), ProfileId(5));
+        assert_eq!(index.take_next_id(), ProfileId(6));
+    }
+
+    fn split(args: &[&str]) -> Result<(Option<PathBuf>, Vec<String>), Box<dyn Error>> {
+        Storage::split_data_dir_arg(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn split_data_dir_arg_takes_the_path_after_the_flag() {
+        let (path, remaining) = split(&["reset", "--data-dir", "/tmp/kanji", "1"]).unwrap();
+        assert_eq!(path, Some(PathBuf::from("/tmp/kanji")));
+        assert_eq!(remaining, ["reset", "1"]);
+
+        let (path, remaining) = split(&["--data-dir=/tmp/kanji", "help"]).unwrap();
+        assert_eq!(path, Some(PathBuf::from("/tmp/kanji")));
+        assert_eq!(remaining, ["help"]);
+
+        let (path, remaining) = split(&["profiles", "list"]).unwrap();
+        assert_eq!(path, None);
+        assert_eq!(remaining, ["profiles", "list"]);
+    }
+
+    #[test]
+    fn split_data_dir_arg_rejects_a_missing_path() {
+        assert!(split(&["reset", "1", "--yes", "--data-dir"]).is_err());
+        assert!(split(&["reset", "1", "--data-dir", "--yes"]).is_err());
+        assert!(split(&["--data-dir=", "help"]).is_err());
+        assert!(split(&["--data-dir", "a", "--data-dir", "b"]).is_err());
+    }
+}
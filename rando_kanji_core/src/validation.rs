@@ -0,0 +1,313 @@
+use std::{collections::HashMap, error::Error, fmt::Display, fs, path::Path};
+
+use crate::kanji::KanjiRecord;
+
+/// Rows with errors are skipped when a kanji database is loaded, rows with warnings are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found in a row of a kanji database file
+pub struct Problem {
+    pub line: u64,
+    pub column: Option<String>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        match &self.column {
+            Some(column) => write!(
+                f,
+                "line {}, column {}: {}: {}",
+                self.line, column, severity, self.message
+            ),
+            None => write!(f, "line {}: {}: {}", self.line, severity, self.message),
+        }
+    }
+}
+
+impl Problem {
+    fn new(line: u64, column: Option<&str>, severity: Severity, message: &str) -> Self {
+        Self {
+            line,
+            column: column.map(str::to_string),
+            severity,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// The valid kanji records of a database file and the problems found in it
+pub struct Validation {
+    pub kanjis: Vec<KanjiRecord>,
+    pub problems: Vec<Problem>,
+}
+
+impl Validation {
+    const BYTE_ORDER_MARK: &'static [u8] = b"\xEF\xBB\xBF";
+    const REQUIRED_COLUMNS: [&'static str; 9] = [
+        "id",
+        "kanji",
+        "jlpt",
+        "joyo_reading",
+        "reading",
+        "on",
+        "on_trans",
+        "kun",
+        "kun_trans",
+    ];
+    const OPTIONAL_COLUMNS: [&'static str; 8] = [
+        "strokes",
+        "grade",
+        "frequency",
+        "radical",
+        "nanori",
+        "fr_trans",
+        "es_trans",
+        "pt_trans",
+    ];
+    const MAX_JLPT_LEVEL: u8 = 5;
+
+    /// Check every row of a kanji database. Fails only if the file can't be read
+    /// or a required column is missing
+    pub fn check(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let bytes = fs::read(path)?;
+        let mut problems = Vec::new();
+        if bytes.starts_with(Self::BYTE_ORDER_MARK) {
+            problems.push(Problem::new(
+                1,
+                None,
+                Severity::Warning,
+                "the file starts with a byte order mark",
+            ));
+        }
+
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(KanjiRecord::DELIMITER)
+            .from_reader(bytes.as_slice());
+        let headers = reader.headers()?.clone();
+        for column in Self::REQUIRED_COLUMNS {
+            if !headers.iter().any(|header| header == column) {
+                return Err(format!("Missing column: {}", column).into());
+            }
+        }
+        for header in headers.iter() {
+            if !Self::REQUIRED_COLUMNS.contains(&header)
+                && !Self::OPTIONAL_COLUMNS.contains(&header)
+            {
+                problems.push(Problem::new(
+                    1,
+                    Some(header),
+                    Severity::Warning,
+                    "unknown column",
+                ));
+            }
+        }
+
+        let mut kanjis = Vec::new();
+        let mut kanji_lines = HashMap::new();
+        let mut id_lines = HashMap::new();
+        for result in reader.records() {
+            let row = match result {
+                Ok(row) => row,
+                Err(err) => {
+                    let line = err.position().map_or(0, |position| position.line());
+                    problems.push(Problem::new(line, None, Severity::Error, &err.to_string()));
+                    continue;
+                }
+            };
+            let line = row.position().map_or(0, |position| position.line());
+            for (header, field) in headers.iter().zip(row.iter()) {
+                if field.trim() != field {
+                    problems.push(Problem::new(
+                        line,
+                        Some(header),
+                        Severity::Warning,
+                        "leading or trailing whitespace",
+                    ));
+                }
+            }
+
+            let record = match row.deserialize::<KanjiRecord>(Some(&headers)) {
+                Ok(record) => record,
+                Err(err) => {
+                    let (column, message) = match err.kind() {
+                        csv::ErrorKind::Deserialize { err, .. } => (
+                            err.field().and_then(|index| headers.get(index as usize)),
+                            err.kind().to_string(),
+                        ),
+                        _ => (None, err.to_string()),
+                    };
+                    problems.push(Problem::new(line, column, Severity::Error, &message));
+                    continue;
+                }
+            };
+            let row_problems = Self::check_record(&record, line, &kanji_lines, &id_lines);
+            let has_errors = row_problems
+                .iter()
+                .any(|problem| problem.severity == Severity::Error);
+            problems.extend(row_problems);
+            if !has_errors {
+                kanji_lines.insert(record.kanji, line);
+                id_lines.insert(record.id, line);
+                kanjis.push(record);
+            }
+        }
+        Ok(Self { kanjis, problems })
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.problems
+            .iter()
+            .filter(|problem| problem.severity == Severity::Error)
+            .count()
+    }
+
+    fn check_record(
+        record: &KanjiRecord,
+        line: u64,
+        kanji_lines: &HashMap<char, u64>,
+        id_lines: &HashMap<u32, u64>,
+    ) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        if let Some(first_line) = kanji_lines.get(&record.kanji) {
+            let message = format!(
+                "duplicate kanji {}, first on line {}",
+                record.kanji, first_line
+            );
+            problems.push(Problem::new(line, Some("kanji"), Severity::Error, &message));
+        }
+        if let Some(first_line) = id_lines.get(&record.id) {
+            let message = format!("duplicate id {}, first on line {}", record.id, first_line);
+            problems.push(Problem::new(line, Some("id"), Severity::Error, &message));
+        }
+        if record.joyo_reading.trim().is_empty() {
+            problems.push(Problem::new(
+                line,
+                Some("joyo_reading"),
+                Severity::Error,
+                "no reading",
+            ));
+        }
+        if record.jlpt > Self::MAX_JLPT_LEVEL {
+            let message = format!(
+                "JLPT level {} is not between 0 and {}",
+                record.jlpt,
+                Self::MAX_JLPT_LEVEL
+            );
+            problems.push(Problem::new(
+                line,
+                Some("jlpt"),
+                Severity::Warning,
+                &message,
+            ));
+        }
+        let is_missing = |field: &str| matches!(field.trim(), "" | "-");
+        if is_missing(&record.on_reading) && is_missing(&record.kun_reading) {
+            problems.push(Problem::new(
+                line,
+                Some("on"),
+                Severity::Warning,
+                "no rōmaji reading",
+            ));
+        }
+        if is_missing(&record.on_trans) && is_missing(&record.kun_trans) {
+            problems.push(Problem::new(
+                line,
+                Some("on_trans"),
+                Severity::Warning,
+                "no meaning",
+            ));
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    const HEADER: &str = "id;kanji;jlpt;joyo_reading;reading;on;on_trans;kun;kun_trans";
+
+    /// Check the text as the content of a kanji database file
+    fn check(name: &str, text: &str) -> Result<Validation, Box<dyn Error>> {
+        let path = env::temp_dir().join(format!("rando_kanji_{}_{}.csv", process::id(), name));
+        fs::write(&path, text).unwrap();
+        let validation = Validation::check(&path);
+        fs::remove_file(&path).unwrap();
+        validation
+    }
+
+    fn problems(validation: &Validation) -> Vec<(u64, Option<&str>, Severity)> {
+        validation
+            .problems
+            .iter()
+            .map(|problem| (problem.line, problem.column.as_deref(), problem.severity))
+            .collect()
+    }
+
+    #[test]
+    fn check_accepts_valid_rows() {
+        let text = format!(
+            "{}\n1;亜;1;ア;;a;Asia;-;-\n2;哀;1;アイ、あわ-れ;;ai;pity;awa(re);grief\n",
+            HEADER
+        );
+        let validation = check("valid", &text).unwrap();
+        assert!(validation.problems.is_empty());
+        assert_eq!(validation.kanjis.len(), 2);
+        assert_eq!(validation.error_count(), 0);
+    }
+
+    #[test]
+    fn check_skips_rows_with_errors() {
+        let text = format!(
+            "\u{FEFF}{};note\n\
+             1;亜;1;ア;;a;Asia;-;-;\n\
+             2;亜;1;ア;;a;Asia;-;-;\n\
+             1;哀;1;アイ;;ai;pity;-;-;\n\
+             3;愛;N2;アイ;;ai;love;-;-;\n\
+             4;悪;1; ;;aku;bad;-;-;\n\
+             5;握;7;アク;;-;-;-;-;\n",
+            HEADER
+        );
+        let validation = check("errors", &text).unwrap();
+        assert_eq!(
+            problems(&validation),
+            [
+                (1, None, Severity::Warning),
+                (1, Some("note"), Severity::Warning),
+                (3, Some("kanji"), Severity::Error),
+                (4, Some("id"), Severity::Error),
+                (5, Some("jlpt"), Severity::Error),
+                (6, Some("joyo_reading"), Severity::Warning),
+                (6, Some("joyo_reading"), Severity::Error),
+                (7, Some("jlpt"), Severity::Warning),
+                (7, Some("on"), Severity::Warning),
+                (7, Some("on_trans"), Severity::Warning),
+            ]
+        );
+        assert_eq!(validation.error_count(), 4);
+        let kanjis = validation
+            .kanjis
+            .iter()
+            .map(|record| record.kanji)
+            .collect::<Vec<char>>();
+        assert_eq!(kanjis, ['亜', '握']);
+    }
+
+    #[test]
+    fn check_fails_without_required_columns() {
+        let text = "id;kanji;jlpt\n1;亜;1\n";
+        assert!(check("missing_column", text).is_err());
+    }
+}
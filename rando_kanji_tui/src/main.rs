@@ -0,0 +1,157 @@
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    process,
+    time::SystemTime,
+};
+
+use rando_kanji_core::{
+    config::{Config, QuizMode},
+    scheduler::ReviewSummary,
+    session::QuizSession,
+    storage::Storage,
+};
+
+const TITLE: &str = "Rando Kanji ・ ランド漢字";
+const QUIT_COMMAND: &str = "q";
+const NEW_PROFILE_COMMAND: &str = "n";
+
+/// Terminal front-end running the same quiz as the play state of the graphical app
+fn main() {
+    if let Err(err) = Storage::take_data_dir_arg(env::args().skip(1)) {
+        eprintln!("Error: {}", err);
+        process::exit(2);
+    }
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+
+    println!("{}\n", TITLE);
+    let config = match select_profile(&mut input) {
+        Some(config) => config,
+        None => return,
+    };
+    println!(
+        "\n{}\n{}\n",
+        config.profile.name,
+        ReviewSummary::new(config.statistics(), SystemTime::now())
+    );
+    println!("Type '{}' to quit\n", QUIT_COMMAND);
+
+    let mut session = QuizSession::new(config);
+    while ask_question(&mut session, &mut input) {
+        // Save after every question so that a dropped connection doesn't lose progress
+        save_profile(&session.config);
+    }
+    save_profile(&session.config);
+}
+
+fn save_profile(config: &Config) {
+    if let Err(err) = config.save() {
+        println!("Could not save profile: {}", err);
+    }
+}
+
+fn select_profile(input: &mut impl BufRead) -> Option<Config> {
+    let profiles = Config::profiles();
+    for profile in profiles.iter() {
+        println!("{}) {}", profile.id.0, profile.name);
+    }
+    println!("{}) New profile", NEW_PROFILE_COMMAND);
+    loop {
+        let line = prompt(input, "Select profile [Enter for last used]: ")?;
+        if line.is_empty() {
+            return Some(Config::load_from_file());
+        }
+        let config = if line == NEW_PROFILE_COMMAND {
+            let name = prompt(input, "Profile name: ")?;
+            match Config::create_profile(&name) {
+                Ok(config) => config,
+                Err(err) => {
+                    println!("Could not create profile: {}", err);
+                    continue;
+                }
+            }
+        } else {
+            let profile = line
+                .parse::<u32>()
+                .ok()
+                .and_then(|id| profiles.iter().find(|profile| profile.id.0 == id));
+            match profile.map(|profile| Config::try_load_by_profile(profile.id)) {
+                Some(Ok(config)) => config,
+                Some(Err(err)) => {
+                    println!("Could not load profile: {}", err);
+                    continue;
+                }
+                None => {
+                    println!("No such profile");
+                    continue;
+                }
+            }
+        };
+        config.mark_last_used();
+        return Some(config);
+    }
+}
+
+/// Ask a single kanji until it is answered correctly. Returns false when the learner quits
+fn ask_question(session: &mut QuizSession, input: &mut impl BufRead) -> bool {
+    let is_typed = session.config.quiz_mode == QuizMode::TypedReading;
+    let question = match session.next_question() {
+        Some(question) => question,
+        None => {
+            println!("Every kanji of the deck is suspended, remove some from the excluded kanji of the profile");
+            return false;
+        }
+    };
+    println!("{}", question.prompt);
+    for (i, option) in question.options.iter().enumerate() {
+        println!("  {}) {}", i + 1, option);
+    }
+    let correct_option = question.correct_option.clone();
+    let option_count = question.options.len();
+
+    loop {
+        let line = match prompt(input, "> ") {
+            Some(line) => line,
+            None => return false,
+        };
+        if line == QUIT_COMMAND {
+            return false;
+        }
+        let is_correct = if is_typed {
+            if line.is_empty() {
+                continue;
+            }
+            session.answer_typed(&line)
+        } else {
+            match line.parse::<usize>() {
+                Ok(number) if (1..=option_count).contains(&number) => {
+                    session.answer_option(number - 1)
+                }
+                _ => {
+                    println!("Choose an option by its number");
+                    continue;
+                }
+            }
+        };
+        if is_correct {
+            println!("正解! Correct\n");
+            return true;
+        } else if is_typed {
+            println!("✗ Wrong, the readings are {}", correct_option);
+        } else {
+            println!("✗ Wrong, try again");
+        }
+    }
+}
+
+/// Read a trimmed line from the input, None at the end of input
+fn prompt(input: &mut impl BufRead, text: &str) -> Option<String> {
+    print!("{}", text);
+    io::stdout().flush().ok()?;
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
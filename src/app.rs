@@ -1,6 +1,13 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, time::SystemTime};
 
 use egui_sfml::SfEgui;
+use rando_kanji_core::{
+    config::Config,
+    scheduler::ReviewSummary,
+    session::QuizSession,
+    storage::Storage,
+    statistics::ProgressStatistics,
+};
 use sfml::{
     graphics::{Color, Font, RenderTarget, RenderWindow, Text, View},
     system::{Vector2f, Vector2i, Vector2u},
@@ -9,22 +16,21 @@ use sfml::{
 
 use crate::{
     audio::{SoundBuffers, SoundPlayers},
-    config::{Config, ProfileEnum},
     game_state::GameState,
     window::{
         self,
-        ui::{self, AnswerData, TextButton, TextDescriptor},
+        ui::{self, AnswerData, BrowseScreen, KanjiDetails, OptionsScreen, TextButton, TextDescriptor, TypedAnswer},
     },
 };
-use crate::{config::StatValue, kanji::KanjiDealer, utils, window::ui::ButtonAction::CheckAnswer};
+use crate::{
+    utils::{self, Timer}, window::ui::ButtonAction::CheckAnswer,
+};
 
 pub struct App<'a> {
     pub window: RenderWindow,
     pub main_view: SfBox<View>,
     pub game_view: SfBox<View>,
-    pub config: Config,
-    pub selected_profile: ProfileEnum,
-    pub kanji_dealer: KanjiDealer,
+    pub session: QuizSession,
     pub font: SfBox<Font>,
     pub texts: Vec<TextDescriptor>,
     pub buttons: Rc<RefCell<Vec<TextButton<'a>>>>,
@@ -32,7 +38,14 @@ pub struct App<'a> {
     pub is_switching_state: bool,
     pub sound_players: SoundPlayers<'a>,
     pub egui: SfEgui,
-    pub showing_confirm_dialog: bool,
+    pub review_summary: ReviewSummary,
+    pub typed_answer: TypedAnswer,
+    pub progress_statistics: ProgressStatistics,
+    pub options: OptionsScreen,
+    pub browse: BrowseScreen,
+    pub autosave_timer: Timer,
+    pub save_error: Option<String>, // Shown until the learner dismisses it
+    pub kanji_details: Option<KanjiDetails>, // Shown after a correct answer until the learner dismisses it
 }
 
 impl<'a> App<'a> {
@@ -42,10 +55,8 @@ impl<'a> App<'a> {
     pub const INIT_WIN_SIZE: Vector2u = Vector2u::new(1600, 900);
     pub const GAME_BACKGROUND_COLOR: Color = Color::rgb(10, 10, 10);
     pub const MENU_BACKGROUND_COLOR: Color = Color::BLACK;
-    pub const KANJI_DB_PATH: &'static str = "res/kanji_db.csv";
-    pub const FONT_PATH: &'static str = "res/font/Honoka-Shin-Antique-Maru_R.otf";
-    pub const CONFIG_PATH: &'static str = "./config";
-    pub const CONFIG_FILE_EXTENSION: &'static str = ".json";
+    pub const FONT_FILE: &'static str = "font/Honoka-Shin-Antique-Maru_R.otf"; // In the resource directory
+    pub const AUTOSAVE_INTERVAL_SECS: f32 = 60.0;
 
     pub fn new(sounds: &'a SoundBuffers) -> Self {
         let mut window = window::init();
@@ -61,9 +72,10 @@ impl<'a> App<'a> {
         window.set_view(&main_view);
 
         let config = Config::load_from_file();
-        let selected_profile = config.profile.id;
-        let kanji_dealer = KanjiDealer::new();
-        let font = Font::from_file(App::FONT_PATH).expect("Could not load font");
+        let review_summary = ReviewSummary::new(config.statistics(), SystemTime::now());
+        let session = QuizSession::new(config);
+        let font_path = Storage::resource_dir().join(App::FONT_FILE);
+        let font = Font::from_file(&font_path.to_string_lossy()).expect("Could not load font");
         let texts = Vec::new();
         let buttons = Rc::new(RefCell::new(Vec::new()));
         let current_state = GameState::Menu;
@@ -76,9 +88,7 @@ impl<'a> App<'a> {
             window,
             main_view,
             game_view,
-            config,
-            selected_profile,
-            kanji_dealer,
+            session,
             font,
             texts,
             buttons,
@@ -86,47 +96,92 @@ impl<'a> App<'a> {
             is_switching_state,
             sound_players: sounds,
             egui,
-            showing_confirm_dialog: false,
+            review_summary,
+            typed_answer: TypedAnswer::default(),
+            progress_statistics: ProgressStatistics::default(),
+            options: OptionsScreen::default(),
+            browse: BrowseScreen::default(),
+            autosave_timer: Timer::new(App::AUTOSAVE_INTERVAL_SECS),
+            save_error: None,
+            kanji_details: None,
+        }
+    }
+
+    /// Save the profile during play so that a crash loses at most a minute of progress
+    pub fn save_profile(&mut self) {
+        if let Err(err) = self.session.config.save() {
+            self.save_error = Some(format!("Could not save profile: {}", err));
         }
     }
 
     fn check_answer(&mut self, button: &mut TextButton, ans_data: &AnswerData) {
-        if ans_data.index_to_test == ans_data.correct_index {
+        if self.session.answer_option(ans_data.index_to_test as usize) {
             // If correct reading choice
             self.sound_players.correct_ans.play();
-            let entry = self
-                .config
-                .answer_statistics
-                .entry(ans_data.kanji)
-                .or_insert_with(StatValue::default);
-            entry.learning_index += 1;
-            if entry.learning_index >= self.config.learning_index_threshold {
-                entry.review_interval += Config::REVIEW_INTERVAL_STEP;
-            }
-            self.change_state(GameState::Play); // Show a new kanji
+            self.finish_question();
         } else {
             // Incorrect reading choice
             self.sound_players.incorrect_ans.play();
-            let entry = self
-                .config
-                .answer_statistics
-                .entry(ans_data.kanji)
-                .or_insert_with(StatValue::default);
-            entry.learning_index -= 1;
             button.set_color(Color::RED, true);
         }
     }
 
+    pub fn check_typed_answer(&mut self) {
+        self.typed_answer.is_submitted = false;
+        let readings = match self.session.question() {
+            Some(question) => question.correct_option.clone(),
+            None => return,
+        };
+
+        if self.session.answer_typed(&self.typed_answer.text) {
+            self.sound_players.correct_ans.play();
+            self.finish_question();
+        } else {
+            // Reveal the readings and let the learner try again
+            self.sound_players.incorrect_ans.play();
+            self.typed_answer.text.clear();
+            if !self.texts.iter().any(|text| text.string == readings) {
+                let mut text = TextDescriptor::new(
+                    &readings,
+                    Vector2f::new(self.window.size().x as f32 / 2.0, 250.0),
+                    Color::RED,
+                    true,
+                );
+                text.font_size = App::FONT_SIZE;
+                self.texts.push(text);
+            }
+        }
+    }
+
+    /// Show the details of the answered kanji if the profile shows them, otherwise a new kanji
+    fn finish_question(&mut self) {
+        let kanji = match self.session.question() {
+            Some(question) if self.session.config.show_kanji_details => question.kanji,
+            _ => {
+                self.change_state(GameState::Play);
+                return;
+            }
+        };
+        self.kanji_details = Some(KanjiDetails {
+            history: self.session.history(kanji),
+            record: self.session.kanji_dealer.find_record_by_char(&kanji).clone(),
+            stat: self.session.config.statistics().get(&kanji).cloned(),
+        });
+    }
+
+    /// Continue with a new kanji once the details of the answered one have been seen
+    pub fn dismiss_kanji_details(&mut self) {
+        if self.kanji_details.take().is_some() {
+            self.change_state(GameState::Play);
+        }
+    }
+
     pub fn update_buttons(&mut self, mouse_pos: Vector2i, check_press: bool) {
         let mapped_mouse_pos = utils::vector2f_to_vector2i(
             self.window.map_pixel_to_coords(mouse_pos, &self.game_view),
         );
 
         for button in self.buttons.clone().borrow_mut().iter_mut() {
-            // Check if a button overlaps the window and zoom out accordingly
-            if button.get_width() > self.game_view.size().x {
-                self.set_view_zoom(1.1);
-            }
             if check_press {
                 if let Some(CheckAnswer(data)) = button.check_for_mouse_press(mapped_mouse_pos) {
                     self.check_answer(button, &data)
@@ -143,6 +198,9 @@ impl<'a> App<'a> {
     }
 
     pub fn draw(&mut self) {
+        if self.kanji_details.is_some() {
+            return; // The details are drawn over an empty screen
+        }
         // Draw texts
         self.window.set_view(&self.main_view);
         let mut text = Text::new("", &self.font, 0);
@@ -157,10 +215,6 @@ impl<'a> App<'a> {
         }
     }
 
-    pub fn set_view_zoom(&mut self, factor: f32) {
-        self.game_view.zoom(factor);
-    }
-
     pub fn reset_zoom(&mut self) {
         self.game_view.set_size(Vector2f::new(
             self.window.size().x as f32,
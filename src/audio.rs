@@ -1,3 +1,4 @@
+use rando_kanji_core::storage::Storage;
 use sfml::{
     audio::{Sound, SoundBuffer},
     SfBox,
@@ -25,8 +26,12 @@ pub struct SoundBuffers {
 impl SoundBuffers {
     pub fn new() -> Self {
         let message = "Error while loading sounds";
-        let correct_ans = SoundBuffer::from_file("res/sounds/correct.wav").expect(message);
-        let incorrect_ans = SoundBuffer::from_file("res/sounds/incorrect.wav").expect(message);
+        let load = |file: &str| {
+            let path = Storage::resource_dir().join(file);
+            SoundBuffer::from_file(&path.to_string_lossy()).expect(message)
+        };
+        let correct_ans = load("sounds/correct.wav");
+        let incorrect_ans = load("sounds/incorrect.wav");
 
         Self {
             correct_ans,
@@ -1,12 +1,21 @@
+use std::time::SystemTime;
+
+use rando_kanji_core::{
+    archive::ProfileArchive,
+    config::{ButtonTextOption, Config, QuizMode},
+    deck::Deck,
+    review_log::ReviewLog,
+    scheduler::ReviewSummary,
+    statistics::ProgressStatistics,
+};
 use sfml::{
-    graphics::{Color, RenderTarget},
+    graphics::{Color, Font, RenderTarget, Transformable},
     system::Vector2f,
 };
 
 use crate::{
     app::App,
-    config::ButtonTextOption,
-    window::ui::{AnswerData, ButtonAction, TextButton, TextDescriptor},
+    window::ui::{self, AnswerData, ButtonAction, TextButton, TextDescriptor},
 };
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -15,10 +24,17 @@ pub enum GameState {
     Menu,
     Options,
     Play,
+    Statistics,
+    Browse,
 }
 
 impl GameState {
     pub const MENU_TITLE: &'static str = "Rando Kanji ・ ランド漢字";
+    const ALL_SUSPENDED_TEXT: &'static str = "All Kanji of the deck are suspended.\nAdd Kanji to the pool in Browse.";
+    const OPTIONS_TOP: f32 = 150.0; // Below the prompt
+    const OPTION_WIDTH_RATIO: f32 = 0.9; // Of the window width
+    const MIN_OPTION_FONT_SIZE: u32 = 20;
+    const OPTION_FONT_SIZE_STEP: u32 = 5;
 
     pub fn init_menu_state(app: &mut App) {
         app.window.set_framerate_limit(App::MENU_FPS_LIMIT);
@@ -34,6 +50,8 @@ impl GameState {
         );
         title.font_size = App::FONT_SIZE;
         app.texts.push(title);
+
+        app.review_summary = ReviewSummary::new(app.session.config.statistics(), SystemTime::now());
     }
 
     pub fn init_play_state(app: &mut App) {
@@ -42,60 +60,136 @@ impl GameState {
         app.texts.clear();
         app.buttons.borrow_mut().clear();
 
-        app.kanji_dealer.update_kanji_pool(&mut app.config);
-
-        let (correct_index, candidates) = app.kanji_dealer.deal_kanji_candidates(&mut app.config);
+        let quiz_mode = app.session.config.quiz_mode;
+        let text_option = app.session.config.button_text_option;
+        let question = match app.session.next_question() {
+            Some(question) => question,
+            None => {
+                let mut text = TextDescriptor::new(
+                    Self::ALL_SUSPENDED_TEXT,
+                    Vector2f::new(app.window.size().x as f32 / 2.0, app.window.size().y as f32 / 2.0),
+                    Color::WHITE,
+                    true,
+                );
+                text.font_size = App::FONT_SIZE / 2;
+                app.texts.push(text);
+                return;
+            }
+        };
 
-        let mut kanji_text = TextDescriptor::new(
-            &candidates[correct_index as usize].kanji.to_string(),
-            Vector2f::new(app.window.size().x as f32 / 2.0, 50.0),
-            Color::WHITE,
-            true,
-        );
-        kanji_text.font_size = 50 + App::FONT_SIZE;
-        app.texts.push(kanji_text);
-
-        let mut last_btn_height = 0.0;
-
-        for (i, option) in candidates.iter().enumerate() {
-            let button_string: String = if i as u8 == correct_index {
-                match app.config.button_text_option {
-                    ButtonTextOption::Kana => candidates[correct_index as usize]
-                        .joyo_reading
-                        .trim()
-                        .to_string(),
-                    ButtonTextOption::Romaji => candidates[correct_index as usize].as_romaji(),
-                    ButtonTextOption::Meaning => candidates[correct_index as usize].as_meaning(),
-                }
-            } else {
-                match app.config.button_text_option {
-                    ButtonTextOption::Kana => option.joyo_reading.trim().to_string(),
-                    ButtonTextOption::Romaji => option.as_romaji(),
-                    ButtonTextOption::Meaning => option.as_meaning(),
-                }
+        // The reverse quiz mode asks for the kanji of a meaning or reading
+        let prompt_text = if quiz_mode.is_reverse() {
+            let mut text = TextDescriptor::new(
+                &question.prompt,
+                Vector2f::new(app.window.size().x as f32 / 2.0, 80.0),
+                Color::WHITE,
+                true,
+            );
+            text.font_size = match text_option {
+                ButtonTextOption::Meaning => App::FONT_SIZE / 2,
+                _ => App::FONT_SIZE,
             };
+            let max_width = app.window.size().x as f32 * Self::OPTION_WIDTH_RATIO;
+            text.string = ui::wrap_text(&question.prompt, &app.font, text.font_size, max_width);
+            text
+        } else {
+            let mut text = TextDescriptor::new(
+                &question.prompt,
+                Vector2f::new(app.window.size().x as f32 / 2.0, 50.0),
+                Color::WHITE,
+                true,
+            );
+            text.font_size = 50 + App::FONT_SIZE;
+            text
+        };
+        app.texts.push(prompt_text);
+
+        if quiz_mode == QuizMode::TypedReading {
+            app.typed_answer.text.clear();
+            return;
+        }
 
-            let pos = Vector2f::new(app.window.size().x as f32 / 2.0, 200.0 + last_btn_height);
+        // Shrink the options until they fit between the prompt and the bottom of the window
+        let window_size = app.window.size();
+        let max_width = window_size.x as f32 * Self::OPTION_WIDTH_RATIO;
+        let mut font_size = App::FONT_SIZE;
+        let buttons = loop {
+            let buttons = Self::layout_options(&app.font, window_size.x as f32, &question.options, font_size, max_width);
+            let bottom = buttons
+                .last()
+                .map_or(0.0, |button| button.shape.position().y + button.get_height());
+            if bottom <= window_size.y as f32 || font_size <= Self::MIN_OPTION_FONT_SIZE {
+                break buttons;
+            }
+            font_size -= Self::OPTION_FONT_SIZE_STEP;
+        };
+        app.buttons.borrow_mut().extend(buttons);
+    }
+
+    /// Answer option buttons stacked below the prompt, with labels wrapped to the maximum width
+    fn layout_options<'a>(
+        font: &Font,
+        window_width: f32,
+        options: &[String],
+        font_size: u32,
+        max_width: f32,
+    ) -> Vec<TextButton<'a>> {
+        let center_x = window_width / 2.0;
+        let mut top = Self::OPTIONS_TOP;
+        let mut buttons = Vec::new();
+        for (i, option) in options.iter().enumerate() {
             let mut button = TextButton::new(
-                &button_string,
-                pos,
+                &ui::wrap_text(option, font, font_size, max_width),
+                Vector2f::new(center_x, top),
                 Color::WHITE,
                 Color::WHITE,
-                &app.font,
+                font,
+                font_size,
                 ButtonAction::CheckAnswer(AnswerData {
-                    correct_index: correct_index as u8,
                     index_to_test: i as u8,
-                    kanji: candidates[correct_index as usize].kanji,
                 }),
             );
-            button.text.font_size = App::FONT_SIZE;
-            last_btn_height = (i + 1) as f32 * (button.get_height() + 50.0);
-            app.buttons.borrow_mut().push(button);
+            button.set_center(Vector2f::new(center_x, top + button.get_height() / 2.0));
+            top += button.get_height() + font_size as f32;
+            buttons.push(button);
         }
+        buttons
     }
 
     pub fn init_options_state(app: &mut App) {
         app.texts.clear();
         app.buttons.borrow_mut().clear();
+        // The quiz mode or profile may change so a new pool cycle is started afterwards
+        app.session.kanji_dealer.kanji_pool.clear();
+        app.session.reload_history(); // Resetting, importing or merging profiles changes the review log
+        app.options.decks = Deck::list();
+        app.options.profiles = Config::profiles();
+        if app.options.archive_path.is_empty() {
+            app.options.archive_path =
+                format!("{}{}", app.session.config.profile.name, ProfileArchive::FILE_EXTENSION);
+        }
+    }
+
+    pub fn init_statistics_state(app: &mut App) {
+        app.texts.clear();
+        app.buttons.borrow_mut().clear();
+        app.session.kanji_dealer.sync_deck(&app.session.config);
+
+        let entries = match ReviewLog::read_or_empty(&app.session.config.review_log_path()) {
+            Ok(entries) => entries,
+            Err(err) => {
+                app.save_error = Some(format!("Could not read review log: {}", err));
+                Vec::new()
+            }
+        };
+        app.progress_statistics =
+            ProgressStatistics::new(&entries, &app.session.config, &app.session.kanji_dealer.kanjis);
+    }
+
+    pub fn init_browse_state(app: &mut App) {
+        app.texts.clear();
+        app.buttons.borrow_mut().clear();
+        app.session.kanji_dealer.sync_deck(&app.session.config);
+        app.browse.results = None; // The statistics may have changed since the last search
     }
 }
@@ -1,7 +1,8 @@
 use rand::{Rng, distributions::Uniform, seq::IteratorRandom};
+use rando_kanji_core::kanji::KanjiRecord;
 use sfml::{system::Vector2f, graphics::Color};
 
-use crate::{window::ui::TextDescriptor, app::App, kanji::KanjiRecord, game_state::GameState, utils::Timer};
+use crate::{window::ui::TextDescriptor, app::App, game_state::GameState, utils::Timer};
 
 pub struct KanjiFall {
     spawn_timer: Timer,
@@ -1,20 +1,25 @@
+use std::{env, process};
+
 use app::App;
 use audio::SoundBuffers;
-use game_state::GameState::{self, Menu, Options, Play};
+use game_state::GameState::{self, Browse, Menu, Options, Play, Statistics};
 use kanji_fall::KanjiFall;
+use rando_kanji_core::storage::Storage;
 use sfml::graphics::RenderTarget;
 use window::ui;
 
 mod app;
 mod audio;
-mod config;
 mod game_state;
-mod kanji;
 mod utils;
 mod window;
 mod kanji_fall;
 
 fn main() {
+    if let Err(err) = Storage::take_data_dir_arg(env::args().skip(1)) {
+        eprintln!("Error: {}", err);
+        process::exit(2);
+    }
     let sounds = SoundBuffers::new();
     let mut app = App::new(&sounds);
     app.change_state(GameState::Menu);
@@ -27,20 +32,30 @@ fn main() {
                 Menu => GameState::init_menu_state(&mut app),
                 Options => GameState::init_options_state(&mut app),
                 Play => GameState::init_play_state(&mut app),
+                Statistics => GameState::init_statistics_state(&mut app),
+                Browse => GameState::init_browse_state(&mut app),
             }
             app.is_switching_state = false;
         }
         window::handle_events(&mut app);
         if app.current_state == GameState::Menu { // Update falling kanji animation in the background
-            kanji_fall.update(&mut app.texts, &app.kanji_dealer.kanjis);
+            kanji_fall.update(&mut app.texts, &app.session.kanji_dealer.kanjis);
             app.window.clear(App::MENU_BACKGROUND_COLOR);
         } else {
             app.window.clear(App::GAME_BACKGROUND_COLOR);
         }
         app.draw();
         ui::draw(&mut app);
+        if app.typed_answer.is_submitted {
+            app.check_typed_answer();
+        }
+        if app.current_state == GameState::Play && app.autosave_timer.check() {
+            app.save_profile();
+        }
         app.window.display();
     }
     // Save current profile configurations to disk
-    app.config.save();
+    if let Err(err) = app.session.config.save() {
+        eprintln!("Could not save profile: {}", err);
+    }
 }
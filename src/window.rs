@@ -25,6 +25,10 @@ pub fn handle_events(app: &mut App) {
             | Event::KeyPressed {
                 code: Key::Escape, ..
             } => app.window.close(),
+            // Any click or key moves on from the details of the answered kanji
+            Event::MouseButtonPressed { .. } | Event::KeyPressed { .. } if app.kanji_details.is_some() => {
+                app.dismiss_kanji_details()
+            }
             Event::MouseButtonPressed { button: _, x, y } => {
                 app.update_buttons(Vector2i::new(x, y), true)
             }
@@ -35,7 +39,25 @@ pub fn handle_events(app: &mut App) {
 }
 
 pub mod ui {
-    use egui_sfml::egui::{self, style::Margin, Context, FontFamily, FontId, TextStyle};
+    use egui_sfml::egui::{
+        self,
+        plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints},
+        style::Margin,
+        Context, FontFamily, FontId, TextStyle,
+    };
+    use std::{mem, path::Path, time::SystemTime};
+
+    use rando_kanji_core::{
+        archive::ProfileArchive,
+        browse::{KanjiSearch, KanjiSort, KanjiState},
+        config::{ButtonTextOption, Config, Profile, QuizMode, StatValue},
+        deck::{Deck, DeckInfo},
+        distractor::DistractorStrategy,
+        kanji::KanjiRecord,
+        reading::Reading,
+        review_log::ReviewLog, romaji, scheduler::{ReviewSummary, SchedulerKind}, session::QuizSession,
+        statistics::{KanjiHistory, ProgressStatistics},
+    };
     use sfml::{
         graphics::{
             Color, Font, Rect, RectangleShape, RenderTarget, RenderWindow, Shape, Text,
@@ -46,33 +68,63 @@ pub mod ui {
 
     use crate::{
         app::App,
-        config::{ButtonTextOption, Config, ProfileEnum},
         game_state::GameState::{self}, utils::Timer,
     };
 
     pub fn draw(app: &mut App) {
-        app.egui.do_frame(|ctx| match app.current_state {
-            GameState::Menu => draw_menu_egui(
-                &mut app.window,
-                &mut app.current_state,
-                &mut app.is_switching_state,
-                ctx,
-            ),
-            GameState::Play => draw_play_egui(
-                &mut app.current_state,
-                &mut app.is_switching_state,
-                &mut app.window,
-                &app.main_view,
-                ctx,
-            ),
-            GameState::Options => draw_options_egui(
-                &mut app.config,
-                &mut app.selected_profile,
-                &mut app.current_state,
-                &mut app.is_switching_state,
-                &mut app.showing_confirm_dialog,
-                ctx,
-            ),
+        app.egui.do_frame(|ctx| {
+            match app.current_state {
+                GameState::Menu => draw_menu_egui(
+                    &mut app.window,
+                    &mut app.current_state,
+                    &mut app.is_switching_state,
+                    &app.review_summary,
+                    ctx,
+                ),
+                GameState::Play => match &app.kanji_details {
+                    Some(details) => {
+                        draw_kanji_details_egui(details, app.session.config.learning_index_threshold, ctx)
+                    }
+                    None => draw_play_egui(
+                        &mut app.current_state,
+                        &mut app.is_switching_state,
+                        &mut app.window,
+                        &app.main_view,
+                        app.session.config.quiz_mode,
+                        &mut app.typed_answer,
+                        ctx,
+                    ),
+                },
+                GameState::Options => {
+                    if let Some(err) = draw_options_egui(
+                        &mut app.session.config,
+                        &mut app.options,
+                        &mut app.current_state,
+                        &mut app.is_switching_state,
+                        ctx,
+                    ) {
+                        app.save_error = Some(err);
+                    }
+                }
+                GameState::Statistics => draw_statistics_egui(
+                    &app.progress_statistics,
+                    &mut app.current_state,
+                    &mut app.is_switching_state,
+                    ctx,
+                ),
+                GameState::Browse => {
+                    if let Some(err) = draw_browse_egui(
+                        &mut app.session,
+                        &mut app.browse,
+                        &mut app.current_state,
+                        &mut app.is_switching_state,
+                        ctx,
+                    ) {
+                        app.save_error = Some(err);
+                    }
+                }
+            }
+            draw_save_error_egui(&mut app.save_error, ctx);
         }).unwrap();
         app.egui.draw(&mut app.window, None);
     }
@@ -122,9 +174,34 @@ pub mod ui {
         is_switching_state: &mut bool,
         window: &mut RenderWindow,
         main_view: &View,
+        quiz_mode: QuizMode,
+        typed_answer: &mut TypedAnswer,
         ctx: &Context,
     ) {
         window.set_view(main_view);
+        if quiz_mode == QuizMode::TypedReading {
+            egui::Area::new("TypedAnswerArea")
+                .movable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::default())
+                .show(ctx, |ui| {
+                    ui.vertical_centered(|ui| {
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut typed_answer.text)
+                                .hint_text("rōmaji")
+                                .desired_width(500.0),
+                        );
+                        response.request_focus();
+                        // Live conversion of the typed rōmaji
+                        let hiragana = romaji::to_hiragana(typed_answer.text.trim());
+                        ui.label(format!("{} ・ {}", hiragana, romaji::hiragana_to_katakana(&hiragana)));
+                        if ui.button("Check").clicked()
+                            || (response.lost_focus() && ui.input().key_pressed(egui::Key::Enter))
+                        {
+                            typed_answer.is_submitted = true;
+                        }
+                    });
+                });
+        }
         egui::Area::new("ButtonArea")
             .movable(false)
             .anchor(egui::Align2::CENTER_BOTTOM, egui::Vec2::default())
@@ -137,84 +214,226 @@ pub mod ui {
             });
     }
 
+    /// Returns the error of a failed profile save to be shown to the learner
     fn draw_options_egui(
         config: &mut Config,
-        selected_profile: &mut ProfileEnum,
+        options: &mut OptionsScreen,
         state: &mut GameState,
         is_switching_state: &mut bool,
-        showing_dialog: &mut bool,
         ctx: &Context,
-    ) {
+    ) -> Option<String> {
+        let mut save_error = None;
         egui::CentralPanel::default()
             .frame(
                 egui::Frame::none()
                 .fill(egui::Color32::from_rgb(App::MENU_BACKGROUND_COLOR.r, App::MENU_BACKGROUND_COLOR.g, App::MENU_BACKGROUND_COLOR.b))
                 .inner_margin(Margin { left: 25.0, right: 0.0 , top: 0.0, bottom: 0.0 }))
             .show(ctx, |ui| {
-                if *showing_dialog {
+                if options.confirm_dialog.is_some() {
                     ui.set_enabled(false);
                 }
                 ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
                     ui.add_space(20.0);
 
-                    ui.radio_value(&mut config.button_text_option, ButtonTextOption::Romaji, "Show rōmaji ローマ字");
-                    ui.radio_value(&mut config.button_text_option, ButtonTextOption::Meaning, "Show meaning 意味表示");
-                    ui.radio_value(&mut config.button_text_option, ButtonTextOption::Kana, "Show kana カナ");
-
-                    ui.add(egui::Slider::new(&mut config.kanji_pool_max_size, 4..=15).text("Kanji pool max size")).on_hover_ui(|ui| {
-                        ui.label("The maximum number of new Kanji to review at once");
-                    });
-                    ui.add(egui::Slider::new(&mut config.learning_index_threshold, 5..=15).text("Learning index threshold")).on_hover_ui(|ui| {
-                        ui.label("A higher index means that it will take more correct answers for a Kanji be considered learned");
-                    });
-                    
-                    ui.add_space(50.0);
+                    // Leave room for the buttons at the bottom of the screen
+                    egui::ScrollArea::vertical().max_height(ui.available_height() - 200.0).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut config.quiz_mode, QuizMode::MultipleChoice, "Multiple choice 選択");
+                            ui.radio_value(&mut config.quiz_mode, QuizMode::TypedReading, "Type reading 入力");
+                            ui.radio_value(&mut config.quiz_mode, QuizMode::Reverse, "Choose Kanji 逆");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut config.button_text_option, ButtonTextOption::Romaji, "Show rōmaji ローマ字");
+                            ui.radio_value(&mut config.button_text_option, ButtonTextOption::Meaning, "Show meaning 意味表示");
+                            ui.radio_value(&mut config.button_text_option, ButtonTextOption::Kana, "Show kana カナ");
+                        });
+                        ui.checkbox(&mut config.show_kanji_details, "Show Kanji details after answers 詳細").on_hover_ui(|ui| {
+                            ui.label("Readings, meanings and your answer history of a Kanji after it has been answered correctly");
+                        });
 
-                    egui::ComboBox::from_label("Select profile")
-                        .width(500.0)
-                        .selected_text(selected_profile.to_string())
-                        .show_ui(ui,|ui| {
-                            let response1 = ui.selectable_value(selected_profile, ProfileEnum::Profile1, "Profile 1");
-                            let response2 = ui.selectable_value(selected_profile, ProfileEnum::Profile2, "Profile 2");
-                            let response3 = ui.selectable_value(selected_profile, ProfileEnum::Profile3, "Profile 3");
-
-                            if response1.clicked() || response2.clicked() || response3.clicked() {
-                                if let Ok(loaded_config) = Config::try_load_by_profile(*selected_profile) {
-                                    *config = loaded_config;
-                                } else {
-                                    *config = Config::default();
+                        ui.add(egui::Slider::new(&mut config.kanji_pool_max_size, 4..=15).text("Kanji pool max size")).on_hover_ui(|ui| {
+                            ui.label("The maximum number of new Kanji to review at once");
+                        });
+                        ui.add(egui::Slider::new(&mut config.learning_index_threshold, 5..=15).text("Learning index threshold")).on_hover_ui(|ui| {
+                            ui.label("A higher index means that it will take more correct answers for a Kanji be considered learned");
+                        });
+                        ui.horizontal(|ui| {
+                            egui::ComboBox::from_label("Scheduling algorithm")
+                                .width(500.0)
+                                .selected_text(config.scheduler.to_string())
+                                .show_ui(ui, |ui| {
+                                    for scheduler in [SchedulerKind::Sm2, SchedulerKind::Leitner, SchedulerKind::Fsrs] {
+                                        ui.selectable_value(&mut config.scheduler, scheduler, scheduler.to_string());
+                                    }
+                                });
+                            if ui.button("Recompute schedule").on_hover_ui(|ui| {
+                                ui.label("Rebuild the Kanji statistics from the review log with the selected algorithm");
+                            }).clicked() {
+                                match ReviewLog::read(&config.review_log_path()) {
+                                    Ok(entries) => {
+                                        let (reverse_entries, entries): (Vec<_>, Vec<_>) = entries.iter().partition(|entry| entry.quiz_mode.is_reverse());
+                                        config.answer_statistics = ReviewLog::replay(entries, config.scheduler.get(), config.learning_index_threshold);
+                                        config.reverse_statistics = ReviewLog::replay(reverse_entries, config.scheduler.get(), config.learning_index_threshold);
+                                    }
+                                    Err(err) => save_error = Some(format!("Could not read review log: {}", err)),
                                 }
                             }
-                        })
-                    });
-                    ui.horizontal(|ui| {
-                        ui.label("Profile name: ");
-                        ui.add(egui::TextEdit::singleline(&mut config.profile.name).desired_width(500.0));
+                        });
+                        egui::ComboBox::from_label("Wrong answer options")
+                            .width(500.0)
+                            .selected_text(config.distractor_strategy.to_string())
+                            .show_ui(ui, |ui| {
+                                for strategy in [
+                                    DistractorStrategy::Random,
+                                    DistractorStrategy::SimilarReading,
+                                    DistractorStrategy::SimilarMeaning,
+                                    DistractorStrategy::SameJlpt,
+                                ] {
+                                    ui.selectable_value(&mut config.distractor_strategy, strategy, strategy.to_string());
+                                }
+                            });
+                        let selected_deck = match &config.deck {
+                            Some(file_name) => options
+                                .decks
+                                .iter()
+                                .find(|deck| deck.file_name == *file_name)
+                                .map_or(file_name.as_str(), |deck| deck.name.as_str()),
+                            None => Deck::MAIN_DECK_NAME,
+                        };
+                        egui::ComboBox::from_label("Deck")
+                            .width(500.0)
+                            .selected_text(selected_deck)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut config.deck, None, Deck::MAIN_DECK_NAME);
+                                for deck in options.decks.iter() {
+                                    ui.selectable_value(&mut config.deck, Some(deck.file_name.clone()), &deck.name);
+                                }
+                            })
+                            .response
+                            .on_hover_ui(|ui| {
+                                ui.label(format!("Custom decks are read from the {} folder", Deck::dir().display()));
+                            });
+                        ui.horizontal(|ui| {
+                            ui.label("JLPT levels: ");
+                            for level in (1..=5).rev() {
+                                ui.checkbox(&mut config.deck_filter.jlpt_levels[level], format!("N{}", level));
+                            }
+                            ui.checkbox(&mut config.deck_filter.jlpt_levels[0], "Other");
+                        }).response.on_hover_ui(|ui| {
+                            ui.label("New Kanji and answer options are only taken from the selected levels");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Excluded Kanji: ");
+                            ui.add(egui::TextEdit::singleline(&mut config.deck_filter.excluded_kanji).desired_width(700.0));
+                        });
+
                         ui.add_space(50.0);
-                        ui.label(format!("Reviewed Kanji: {}", config.answer_statistics.len()));
-                    });
+
+                        egui::ComboBox::from_label("Select profile")
+                            .width(500.0)
+                            .selected_text(config.profile.name.as_str())
+                            .show_ui(ui,|ui| {
+                                for profile in options.profiles.iter() {
+                                    let is_selected = profile.id == config.profile.id;
+                                    if ui.selectable_label(is_selected, &profile.name).clicked()
+                                        && !is_selected
+                                        && save_profile(config, &mut save_error)
+                                    {
+                                        match Config::try_load_by_profile(profile.id) {
+                                            Ok(loaded_config) => *config = loaded_config,
+                                            Err(err) => save_error = Some(format!("Could not load profile: {}", err)),
+                                        }
+                                    }
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            ui.label("Profile name: ");
+                            ui.add(egui::TextEdit::singleline(&mut config.profile.name).desired_width(500.0));
+                            ui.add_space(50.0);
+                            ui.label(format!("Reviewed Kanji: {}", config.answer_statistics.len()));
+                        });
                     
-                    if ui.button("Save profile").clicked() {
-                        config.profile.id = *selected_profile;
-                        config.save();
-                    }
+                        ui.horizontal(|ui| {
+                            if ui.button("Save profile").clicked() {
+                                save_profile(config, &mut save_error);
+                                options.profiles = Config::profiles();
+                            }
+                            if ui.button("New profile").clicked() && save_profile(config, &mut save_error) {
+                                match Config::create_profile("New profile") {
+                                    Ok(new_config) => *config = new_config,
+                                    Err(err) => save_error = Some(format!("Could not create profile: {}", err)),
+                                }
+                                options.profiles = Config::profiles();
+                            }
+                            if ui.button("Duplicate").clicked() && save_profile(config, &mut save_error) {
+                                match config.duplicate_profile(&format!("{} (copy)", config.profile.name)) {
+                                    Ok(new_config) => *config = new_config,
+                                    Err(err) => save_error = Some(format!("Could not duplicate profile: {}", err)),
+                                }
+                                options.profiles = Config::profiles();
+                            }
+                            if ui.button("Delete profile").clicked() {
+                                options.confirm_dialog = Some(ConfirmDialog::DeleteProfile);
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Profile file: ");
+                            ui.add(egui::TextEdit::singleline(&mut options.archive_path).desired_width(700.0));
+                        }).response.on_hover_ui(|ui| {
+                            ui.label("File for moving a profile with its statistics to another computer");
+                        });
+                        ui.horizontal(|ui| {
+                            let path = Path::new(&options.archive_path);
+                            if ui.button("Export").clicked() {
+                                options.archive_status = match ProfileArchive::export(config, path) {
+                                    Ok(()) => format!("Exported to {}", path.display()),
+                                    Err(err) => format!("Could not export profile: {}", err),
+                                };
+                            }
+                            if ui.button("Import as new profile").clicked() && save_profile(config, &mut save_error) {
+                                options.archive_status = match ProfileArchive::import(path) {
+                                    Ok(imported_config) => {
+                                        *config = imported_config;
+                                        format!("Imported {}", config.profile.name)
+                                    }
+                                    Err(err) => format!("Could not import profile: {}", err),
+                                };
+                                options.profiles = Config::profiles();
+                            }
+                            if ui.button("Merge into profile").on_hover_ui(|ui| {
+                                ui.label("Keep the most recent statistics of each Kanji from both profiles");
+                            }).clicked() {
+                                options.archive_status = match ProfileArchive::merge_into(path, config) {
+                                    Ok(summary) => summary.to_string(),
+                                    Err(err) => format!("Could not merge profile: {}", err),
+                                };
+                            }
+                        });
+                        if !options.archive_status.is_empty() {
+                            ui.label(&options.archive_status);
+                        }
+                    });
+                });
 
                 ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
                     ui.add_space(20.0);
                     if ui.button("Back").clicked() {
-                        Config::reset_last_used();
-                        config.was_used_last = true;
+                        config.mark_last_used();
                         *state = GameState::Menu;
                         *is_switching_state = true;
                     }
                     ui.add_space(20.0);
                     if ui.button("Reset profile").clicked() {
-                        *showing_dialog = !*showing_dialog;
+                        options.confirm_dialog = Some(ConfirmDialog::ResetProfile);
                     }
                 });
         });
-        if *showing_dialog {
-            egui::Window::new("Reset profile and its statistics?")
+        if let Some(dialog) = options.confirm_dialog {
+            let title = match dialog {
+                ConfirmDialog::ResetProfile => "Reset profile and its statistics?",
+                ConfirmDialog::DeleteProfile => "Delete profile and its statistics?",
+            };
+            egui::Window::new(title)
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::new(0.0, 100.0))
@@ -222,24 +441,433 @@ pub mod ui {
                     egui::Grid::new("DialogGrid").show(ui, |ui| {
                         ui.horizontal_centered(|ui| {
                             if ui.button("Yes").clicked() {
-                                *config = Config::default();
-                                config.profile.id = *selected_profile;
-                                config.save();
-                                *showing_dialog = false;
+                                match dialog {
+                                    ConfirmDialog::ResetProfile => {
+                                        let profile = config.profile.clone();
+                                        *config = Config::default();
+                                        config.profile = profile;
+                                        if save_profile(config, &mut save_error) {
+                                            ReviewLog::remove(&config.review_log_path());
+                                        }
+                                    }
+                                    ConfirmDialog::DeleteProfile => match Config::delete_profile(config.profile.id) {
+                                        Ok(()) => {
+                                            *config = match Config::profile_ids().first() {
+                                                Some(_) => Config::load_from_file(),
+                                                None => Config::create_profile(&Profile::default().name)
+                                                    .unwrap_or_else(|err| {
+                                                        save_error = Some(format!("Could not create profile: {}", err));
+                                                        Config::default()
+                                                    }),
+                                            };
+                                            options.profiles = Config::profiles();
+                                        }
+                                        Err(err) => save_error = Some(format!("Could not delete profile: {}", err)),
+                                    },
+                                }
+                                options.confirm_dialog = None;
                             }
                             if ui.button("Cancel").clicked() {
-                                *showing_dialog = false;
+                                options.confirm_dialog = None;
                             }
                         });
                     });
                 });
         }
+        save_error
+    }
+
+    /// Save the profile and keep the error if it fails. Returns true when it was saved
+    fn save_profile(config: &Config, save_error: &mut Option<String>) -> bool {
+        match config.save() {
+            Ok(()) => true,
+            Err(err) => {
+                *save_error = Some(format!("Could not save profile: {}", err));
+                false
+            }
+        }
+    }
+
+    fn draw_kanji_details_egui(details: &KanjiDetails, learning_index_threshold: i32, ctx: &Context) {
+        const KANJI_SIZE: f32 = 200.0;
+        const MAX_WIDTH: f32 = 1200.0;
+        const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+        let readings = details.record.readings();
+        let now = SystemTime::now();
+        let history = &details.history;
+        egui::Area::new("KanjiDetailsArea")
+            .movable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::default())
+            .show(ctx, |ui| {
+                ui.set_max_width(MAX_WIDTH);
+                ui.vertical_centered(|ui| {
+                    ui.label(egui::RichText::new(details.record.kanji.to_string()).size(KANJI_SIZE));
+                    if !readings.on.is_empty() {
+                        ui.label(format!("On: {} ・ {}", join_readings(&readings.on, "、"), join_readings(&readings.on_romaji, ", ")));
+                    }
+                    if !readings.kun.is_empty() {
+                        ui.label(format!("Kun: {} ・ {}", join_readings(&readings.kun, "、"), join_readings(&readings.kun_romaji, ", ")));
+                    }
+                    ui.label(readings.meanings.join(", "));
+                    let jlpt = match details.record.jlpt {
+                        0 => "Not in the JLPT".to_string(),
+                        level => format!("JLPT N{}", level),
+                    };
+                    ui.label(egui::RichText::new(jlpt).text_style(TextStyle::Small));
+                    let attributes = kanji_attributes(&details.record);
+                    if !attributes.is_empty() {
+                        ui.label(egui::RichText::new(attributes).text_style(TextStyle::Small));
+                    }
+                    if !details.record.nanori.is_empty() {
+                        let nanori = format!("Names: {}", details.record.nanori);
+                        ui.label(egui::RichText::new(nanori).text_style(TextStyle::Small));
+                    }
+                    for (language, meanings) in [
+                        ("fr", &details.record.fr_trans),
+                        ("es", &details.record.es_trans),
+                        ("pt", &details.record.pt_trans),
+                    ] {
+                        if !meanings.is_empty() {
+                            ui.label(egui::RichText::new(format!("{}: {}", language, meanings)).text_style(TextStyle::Small));
+                        }
+                    }
+                    ui.add_space(30.0);
+
+                    let mut answers = format!("Answered {} times, {:.0}% correct", history.reviews, history.accuracy());
+                    if let Some(first_review) = history.first_review {
+                        let days = now.duration_since(first_review).unwrap_or_default().as_secs_f64() / SECONDS_PER_DAY;
+                        answers += &format!(", first seen {:.0} days ago", days.floor());
+                    }
+                    ui.label(egui::RichText::new(answers).text_style(TextStyle::Small));
+                    if !history.wrong_answers.is_empty() {
+                        let confused = format!("Confused with: {}", history.wrong_answers.join(" ・ "));
+                        ui.label(egui::RichText::new(confused).text_style(TextStyle::Small));
+                    }
+                    let state = match &details.stat {
+                        None => "New".to_string(),
+                        Some(stat) if stat.is_learning() => {
+                            format!("Learning {}/{}", stat.learning_index.max(0), learning_index_threshold)
+                        }
+                        Some(stat) => match stat.due_time.duration_since(now) {
+                            Ok(until_due) => format!("Next review in {:.1} days", until_due.as_secs_f64() / SECONDS_PER_DAY),
+                            Err(_) => "Due for review".to_string(),
+                        },
+                    };
+                    ui.label(egui::RichText::new(state).text_style(TextStyle::Small));
+                    ui.add_space(30.0);
+                    ui.label(egui::RichText::new("Click or press any key for the next Kanji").text_style(TextStyle::Small).weak());
+                });
+            });
+    }
+
+    /// Stroke count, school grade, frequency rank and radical of the extended kanji database
+    fn kanji_attributes(record: &KanjiRecord) -> String {
+        let mut attributes = Vec::new();
+        if let Some(strokes) = record.strokes {
+            attributes.push(format!("{} strokes", strokes));
+        }
+        if let Some(grade) = record.grade {
+            attributes.push(grade_label(grade));
+        }
+        if let Some(frequency) = record.frequency {
+            attributes.push(format!("Frequency #{}", frequency));
+        }
+        if let Some(radical) = record.radical {
+            attributes.push(format!("Radical {}", radical));
+        }
+        attributes.join(" ・ ")
+    }
+
+    /// Grades 1 to 6 are taught in elementary school, 8 in secondary school and 9 or 10 are name kanji
+    fn grade_label(grade: u8) -> String {
+        match grade {
+            1..=6 => format!("Grade {}", grade),
+            8 => "Secondary school".to_string(),
+            _ => "Jinmeiyō".to_string(),
+        }
+    }
+
+    fn join_readings(readings: &[Reading], separator: &str) -> String {
+        readings.iter().map(Reading::to_string).collect::<Vec<String>>().join(separator)
+    }
+
+    fn draw_browse_egui(
+        session: &mut QuizSession,
+        browse: &mut BrowseScreen,
+        state: &mut GameState,
+        is_switching_state: &mut bool,
+        ctx: &Context,
+    ) -> Option<String> {
+        const COLUMNS: usize = 20;
+        const CELL_SIZE: f32 = 65.0;
+        const SELECTED_KANJI_SIZE: f32 = 120.0;
+        const GRADES: [u8; 9] = [1, 2, 3, 4, 5, 6, 8, 9, 10];
+
+        let mut save_error = None;
+        let previous_search = browse.search.clone();
+        egui::CentralPanel::default()
+            .frame(
+                egui::Frame::none()
+                .fill(egui::Color32::from_rgb(App::MENU_BACKGROUND_COLOR.r, App::MENU_BACKGROUND_COLOR.g, App::MENU_BACKGROUND_COLOR.b))
+                .inner_margin(Margin { left: 25.0, right: 25.0, top: 20.0, bottom: 0.0 }))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Search: ");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut browse.search.query)
+                            .hint_text("Kanji, kana, rōmaji or meaning")
+                            .desired_width(700.0),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("JLPT levels: ");
+                    for level in (1..=5).rev() {
+                        ui.checkbox(&mut browse.search.jlpt_levels[level], format!("N{}", level));
+                    }
+                    ui.checkbox(&mut browse.search.jlpt_levels[0], "Other");
+                    ui.add_space(50.0);
+                    let selected_state = match browse.search.state {
+                        Some(kanji_state) => kanji_state.to_string(),
+                        None => "All".to_string(),
+                    };
+                    egui::ComboBox::from_label("State")
+                        .selected_text(selected_state)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut browse.search.state, None, "All");
+                            for kanji_state in KanjiState::ALL {
+                                ui.selectable_value(&mut browse.search.state, Some(kanji_state), kanji_state.to_string());
+                            }
+                        });
+                });
+                // Grades, stroke counts and frequencies are only in the extended kanji database
+                let has_extended_db = session.kanji_dealer.kanjis.iter().any(|record| record.strokes.is_some());
+                ui.add_enabled_ui(has_extended_db, |ui| {
+                    ui.horizontal(|ui| {
+                        let selected_grade = match browse.search.grade {
+                            Some(grade) => grade_label(grade),
+                            None => "All".to_string(),
+                        };
+                        egui::ComboBox::from_label("Grade")
+                            .selected_text(selected_grade)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut browse.search.grade, None, "All");
+                                for grade in GRADES {
+                                    ui.selectable_value(&mut browse.search.grade, Some(grade), grade_label(grade));
+                                }
+                            });
+                        ui.add_space(50.0);
+                        egui::ComboBox::from_label("Sort by")
+                            .selected_text(browse.search.sort.to_string())
+                            .show_ui(ui, |ui| {
+                                for sort in KanjiSort::ALL {
+                                    ui.selectable_value(&mut browse.search.sort, sort, sort.to_string());
+                                }
+                            });
+                    });
+                })
+                .response
+                .on_disabled_hover_text("Build the extended Kanji database with rando_kanji_cli kanjidic import");
+                if browse.search != previous_search {
+                    browse.results = None;
+                }
+                let results = browse
+                    .results
+                    .get_or_insert_with(|| browse.search.results(&session.kanji_dealer.kanjis, &session.config));
+                ui.label(egui::RichText::new(format!("{} Kanji", results.len())).text_style(TextStyle::Small));
+
+                // Leave room for the selected kanji and the back button
+                let rows = results.len().div_ceil(COLUMNS);
+                egui::ScrollArea::vertical()
+                    .max_height(ui.available_height() - 350.0)
+                    .show_rows(ui, CELL_SIZE, rows, |ui, row_range| {
+                        for row in row_range {
+                            ui.horizontal(|ui| {
+                                for kanji in results.iter().skip(row * COLUMNS).take(COLUMNS) {
+                                    let label = egui::SelectableLabel::new(browse.selected == Some(*kanji), kanji.to_string());
+                                    if ui.add_sized([CELL_SIZE, CELL_SIZE], label).clicked() {
+                                        browse.selected = Some(*kanji);
+                                    }
+                                }
+                            });
+                        }
+                    });
+
+                if let Some(kanji) = browse.selected.filter(|kanji| session.kanji_dealer.contains(kanji)) {
+                    let record = session.kanji_dealer.find_record_by_char(&kanji);
+                    let readings = record.readings();
+                    let kana = join_readings(&readings.on.iter().chain(readings.kun.iter()).cloned().collect::<Vec<Reading>>(), "、");
+                    let jlpt = match record.jlpt {
+                        0 => "Not in the JLPT".to_string(),
+                        level => format!("JLPT N{}", level),
+                    };
+                    let kanji_state = KanjiState::of(kanji, &session.config);
+                    let attributes = kanji_attributes(record);
+                    ui.add_space(20.0);
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(kanji.to_string()).size(SELECTED_KANJI_SIZE));
+                        ui.vertical(|ui| {
+                            ui.label(kana);
+                            ui.label(egui::RichText::new(readings.meanings.join(", ")).text_style(TextStyle::Small));
+                            ui.label(egui::RichText::new(format!("{} ・ {}", jlpt, kanji_state)).text_style(TextStyle::Small));
+                            if !attributes.is_empty() {
+                                ui.label(egui::RichText::new(attributes).text_style(TextStyle::Small));
+                            }
+                            let is_added = match kanji_state {
+                                KanjiState::New | KanjiState::Suspended => ui
+                                    .button("Add to pool")
+                                    .on_hover_text("Learn the Kanji with the next questions")
+                                    .clicked(),
+                                KanjiState::Learning | KanjiState::Learned => false,
+                            };
+                            let is_suspended = kanji_state != KanjiState::Suspended
+                                && ui
+                                    .button("Suspend")
+                                    .on_hover_text("Never ask the Kanji until it is added again")
+                                    .clicked();
+                            if is_added {
+                                session.kanji_dealer.add_to_pool(kanji, &mut session.config);
+                                browse.results = None;
+                            } else if is_suspended {
+                                session.kanji_dealer.suspend(kanji, &mut session.config);
+                                browse.results = None;
+                            }
+                        });
+                    });
+                }
+
+                ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                    ui.add_space(20.0);
+                    if ui.button("Back").clicked() {
+                        save_profile(&session.config, &mut save_error);
+                        *state = GameState::Menu;
+                        *is_switching_state = true;
+                    }
+                });
+            });
+        save_error
+    }
+
+    fn draw_save_error_egui(save_error: &mut Option<String>, ctx: &Context) {
+        let message = match save_error {
+            Some(message) => message.clone(),
+            None => return,
+        };
+        egui::Window::new("Profile error")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::Vec2::new(0.0, 50.0))
+            .show(ctx, |ui| {
+                ui.label(message);
+                if ui.button("OK").clicked() {
+                    *save_error = None;
+                }
+            });
+    }
+
+    fn draw_statistics_egui(
+        statistics: &ProgressStatistics,
+        state: &mut GameState,
+        is_switching_state: &mut bool,
+        ctx: &Context,
+    ) {
+        const PLOT_WIDTH: f32 = 700.0;
+        const PLOT_HEIGHT: f32 = 250.0;
+
+        egui::CentralPanel::default()
+            .frame(
+                egui::Frame::none()
+                .fill(egui::Color32::from_rgb(App::MENU_BACKGROUND_COLOR.r, App::MENU_BACKGROUND_COLOR.g, App::MENU_BACKGROUND_COLOR.b))
+                .inner_margin(Margin { left: 25.0, right: 25.0, top: 20.0, bottom: 0.0 }))
+            .show(ctx, |ui| {
+                ui.heading(format!("Total reviews: {}", statistics.total_reviews));
+                egui::Grid::new("StatisticsGrid").num_columns(2).show(ui, |ui| {
+                    ui.vertical(|ui| {
+                        ui.heading("Reviews per day");
+                        Plot::new("ReviewsPerDayPlot")
+                            .width(PLOT_WIDTH)
+                            .height(PLOT_HEIGHT)
+                            .include_y(0.0)
+                            .show(ui, |plot_ui| {
+                                let bars = statistics.daily_reviews.iter().map(|[day, count]| Bar::new(*day, *count)).collect();
+                                plot_ui.bar_chart(BarChart::new(bars).color(egui::Color32::LIGHT_BLUE));
+                            });
+                    });
+                    ui.vertical(|ui| {
+                        ui.heading("Accuracy % over the last 7 days");
+                        Plot::new("AccuracyPlot")
+                            .width(PLOT_WIDTH)
+                            .height(PLOT_HEIGHT)
+                            .include_y(0.0)
+                            .include_y(100.0)
+                            .show(ui, |plot_ui| {
+                                plot_ui.line(Line::new(PlotPoints::new(statistics.rolling_accuracy.clone())).color(egui::Color32::GREEN));
+                            });
+                    });
+                    ui.end_row();
+
+                    ui.vertical(|ui| {
+                        ui.heading("Learned Kanji");
+                        Plot::new("LearnedKanjiPlot")
+                            .width(PLOT_WIDTH)
+                            .height(PLOT_HEIGHT)
+                            .include_y(0.0)
+                            .legend(Legend::default())
+                            .show(ui, |plot_ui| {
+                                plot_ui.line(Line::new(PlotPoints::new(statistics.learned_kanji.clone())).color(egui::Color32::GOLD).name("Recognition"));
+                                plot_ui.line(Line::new(PlotPoints::new(statistics.learned_reverse_kanji.clone())).color(egui::Color32::LIGHT_RED).name("Recall"));
+                            });
+                    });
+                    ui.vertical(|ui| {
+                        ui.heading("Reviewed Kanji by JLPT level");
+                        Plot::new("JlptPlot")
+                            .width(PLOT_WIDTH)
+                            .height(PLOT_HEIGHT)
+                            .include_y(0.0)
+                            .legend(Legend::default())
+                            .x_axis_formatter(|level, _| {
+                                if level == 0.0 {
+                                    "No JLPT".to_string()
+                                } else if level.fract() == 0.0 && (1.0..=5.0).contains(&level) {
+                                    format!("N{}", level)
+                                } else {
+                                    String::new()
+                                }
+                            })
+                            .show(ui, |plot_ui| {
+                                let learned = BarChart::new(statistics.jlpt_levels.iter().map(|progress| {
+                                    Bar::new(progress.level as f64, progress.learned as f64).name(progress.label())
+                                }).collect())
+                                .name("Learned")
+                                .color(egui::Color32::GOLD);
+                                let learning = BarChart::new(statistics.jlpt_levels.iter().map(|progress| {
+                                    Bar::new(progress.level as f64, progress.learning as f64).name(progress.label())
+                                }).collect())
+                                .name("Learning")
+                                .color(egui::Color32::LIGHT_BLUE)
+                                .stack_on(&[&learned]);
+                                plot_ui.bar_chart(learned);
+                                plot_ui.bar_chart(learning);
+                            });
+                    });
+                    ui.end_row();
+                });
+
+                ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                    ui.add_space(20.0);
+                    if ui.button("Back").clicked() {
+                        *state = GameState::Menu;
+                        *is_switching_state = true;
+                    }
+                });
+            });
     }
 
     fn draw_menu_egui(
         window: &mut RenderWindow,
         state: &mut GameState,
         is_switching_state: &mut bool,
+        review_summary: &ReviewSummary,
         ctx: &Context,
     ) {
         egui::Area::new("MenuArea")
@@ -247,6 +875,8 @@ pub mod ui {
             .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::default())
             .show(ctx, |ui| {
                 ui.vertical_centered(|ui| {
+                    ui.label(egui::RichText::new(review_summary.to_string()).text_style(TextStyle::Small));
+                    ui.add_space(50.0);
                     if ui.button("Play").clicked() {
                         *is_switching_state = true;
                         *state = GameState::Play;
@@ -257,6 +887,16 @@ pub mod ui {
                         *state = GameState::Options;
                     }
                     ui.add_space(50.0);
+                    if ui.button("Statistics").clicked() {
+                        *is_switching_state = true;
+                        *state = GameState::Statistics;
+                    }
+                    ui.add_space(50.0);
+                    if ui.button("Browse").clicked() {
+                        *is_switching_state = true;
+                        *state = GameState::Browse;
+                    }
+                    ui.add_space(50.0);
                     if ui.button("Exit").clicked() {
                         window.close();
                     }
@@ -266,9 +906,46 @@ pub mod ui {
 
     #[derive(Clone, Copy)]
     pub struct AnswerData {
-        pub correct_index: u8,
-        pub index_to_test: u8,
-        pub kanji: char,
+        pub index_to_test: u8, // Index of the option in the quiz session question
+    }
+
+    /// Full entry of a kanji shown after it has been answered correctly
+    pub struct KanjiDetails {
+        pub record: KanjiRecord,
+        pub history: KanjiHistory,
+        pub stat: Option<StatValue>, // Scheduling state in the current quiz mode, none for a new kanji
+    }
+
+    /// Search and selection of the kanji browser
+    #[derive(Default)]
+    pub struct BrowseScreen {
+        pub search: KanjiSearch,
+        pub results: Option<Vec<char>>, // Kanji matching the search, none until they are searched again
+        pub selected: Option<char>,
+    }
+
+    /// State of the options screen besides the settings of the profile
+    #[derive(Default)]
+    pub struct OptionsScreen {
+        pub profiles: Vec<Profile>, // Saved profiles
+        pub decks: Vec<DeckInfo>,   // Custom decks
+        pub confirm_dialog: Option<ConfirmDialog>,
+        pub archive_path: String,   // File to export the profile to or import it from
+        pub archive_status: String, // Result of the last export or import
+    }
+
+    /// Confirmation asked before destructive actions in the options
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum ConfirmDialog {
+        ResetProfile,
+        DeleteProfile,
+    }
+
+    /// Answer typed by the learner in the typed reading quiz mode
+    #[derive(Default)]
+    pub struct TypedAnswer {
+        pub text: String, // Answer in rōmaji
+        pub is_submitted: bool,
     }
 
     #[derive(Clone, Copy)]
@@ -276,6 +953,34 @@ pub mod ui {
         CheckAnswer(AnswerData),
     }
 
+    /// Break a string into lines no wider than the maximum width. Lines are broken at spaces,
+    /// words without spaces such as kana readings are broken between characters
+    pub fn wrap_text(string: &str, font: &Font, font_size: u32, max_width: f32) -> String {
+        let width = |line: &str| Text::new(line, font, font_size).global_bounds().width;
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for word in string.split(' ') {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if width(&candidate) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(mem::take(&mut line));
+            }
+            for c in word.chars() {
+                line.push(c);
+                if width(&line) > max_width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(mem::take(&mut line));
+                    line.push(c);
+                }
+            }
+        }
+        lines.push(line);
+        lines.join("\n")
+    }
+
     #[derive(Clone)]
     pub struct TextDescriptor {
         pub string: String,
@@ -329,12 +1034,14 @@ pub mod ui {
             fg_color: Color,
             bg_color: Color,
             font: &Font,
+            font_size: u32,
             action: ButtonAction,
         ) -> Self {
-            let text = TextDescriptor::new(string, pos, fg_color, true);
-            let mut button_dimensions = Text::new(string, font, App::FONT_SIZE).global_bounds();
-            button_dimensions.width += App::FONT_SIZE as f32;
-            button_dimensions.height += App::FONT_SIZE as f32;
+            let mut text = TextDescriptor::new(string, pos, fg_color, true);
+            text.font_size = font_size;
+            let mut button_dimensions = Text::new(string, font, font_size).global_bounds();
+            button_dimensions.width += font_size as f32;
+            button_dimensions.height += font_size as f32;
 
             let mut shape = RectangleShape::from_rect(button_dimensions);
             shape.set_position(pos - button_dimensions.size() / 2.0 + Vector2f::new(3.0, 10.0));
@@ -378,8 +1085,10 @@ pub mod ui {
             self.text.color = color;
         }
 
-        pub fn get_width(&self) -> f32 {
-            self.shape.size().x
+        /// Move the button so that its label is centered on the position
+        pub fn set_center(&mut self, pos: Vector2f) {
+            self.shape.set_position(pos - self.shape.size() / 2.0 + Vector2f::new(3.0, 10.0));
+            self.text.pos = pos;
         }
 
         pub fn get_height(&self) -> f32 {